@@ -385,6 +385,8 @@ impl PyToken {
 /// * text (List[str]): A list of suggested replacements.
 /// * source (str): The ID of the rule that triggered this suggestion.
 /// * message (str): A human-readable message for this suggestion.
+/// * confidence (Optional[float]): The confidence of the rule that triggered this suggestion if there is one.
+/// * sentence_index (int): The index of the sentence this suggestion starts in.
 #[pyclass(name = "Suggestion", module = "nlprule")]
 struct PySuggestion {
     suggestion: Suggestion,
@@ -420,6 +422,16 @@ impl PySuggestion {
     fn message(&self) -> &str {
         &self.suggestion.message
     }
+
+    #[getter]
+    fn confidence(&self) -> Option<f32> {
+        self.suggestion.confidence
+    }
+
+    #[getter]
+    fn sentence_index(&self) -> usize {
+        self.suggestion.sentence_index
+    }
 }
 
 impl From<Suggestion> for PySuggestion {
@@ -909,6 +921,8 @@ impl PyRules {
                     replacements: x.replacements().iter().map(|x| x.to_string()).collect(),
                     start: x.start(),
                     end: x.end(),
+                    confidence: x.suggestion.confidence,
+                    sentence_index: x.suggestion.sentence_index,
                 }
             })
             .collect();
@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nlprule::{
+    rule::engine::composition::{Atom, Composition, MatchGraph},
+    testing,
+    tokenizer::tag::Tagger,
+};
+
+const CHUNKS: [&str; 6] = ["B-NP", "I-NP", "B-VP", "I-VP", "B-PP", "O"];
+const EXCLUDED: [&str; 3] = ["B-NP", "I-NP", "B-VP"];
+
+/// Many rules which check that a token is not part of a noun or verb phrase before matching a word,
+/// with the check either composed from one atom per chunk or combined into one set lookup.
+fn rules(combined: bool) -> Vec<Composition> {
+    (0..50)
+        .map(|i| {
+            let check: Atom = if combined {
+                testing::chunk_set_atom(&EXCLUDED, true)
+            } else {
+                testing::not_atom(testing::or_atom(
+                    EXCLUDED.iter().map(|x| testing::chunk_atom(x)).collect(),
+                ))
+            };
+
+            testing::composition(vec![check, testing::text_atom(&format!("word{}", i % 10))])
+        })
+        .collect()
+}
+
+fn chunk_set(c: &mut Criterion) {
+    let tagger = Tagger::default();
+    let text = (0..40)
+        .map(|i| format!("word{}", i % 13))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut tokens = testing::tokenize(&text, &tagger);
+    for (i, token) in tokens.iter_mut().enumerate() {
+        token.chunks = vec![CHUNKS[i % CHUNKS.len()].to_string()];
+    }
+    let refs: Vec<_> = tokens.iter().collect();
+
+    let mut group = c.benchmark_group("not_in_chunk_set");
+    for &(name, combined) in &[("composed", false), ("combined", true)] {
+        let rules = rules(combined);
+
+        group.bench_function(name, |b| {
+            let mut graph = MatchGraph::default();
+
+            b.iter(|| {
+                let mut n_matches = 0;
+                for rule in &rules {
+                    for start in 0..refs.len() {
+                        n_matches += rule.apply_into(&refs, start, &mut graph) as usize;
+                    }
+                }
+                black_box(n_matches)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, chunk_set);
+criterion_main!(benches);
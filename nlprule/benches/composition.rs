@@ -0,0 +1,49 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use nlprule::{rule::engine::composition::MatchGraph, testing, tokenizer::tag::Tagger};
+
+/// Applies a composition at every position of a long sentence in which the first atoms often match,
+/// so a graph is set up at many positions even though the composition never matches in full.
+/// Full matches are avoided since filling their empty groups scans the whole sentence and would dominate the time.
+fn apply(c: &mut Criterion) {
+    let tagger = Tagger::default();
+    let text = [
+        "the", "cat", "sat", "on", "the", "mat", "near", "the", "dog",
+    ]
+    .iter()
+    .cycle()
+    .take(1000)
+    .copied()
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    let tokens = testing::tokenize(&text, &tagger);
+    let refs: Vec<_> = tokens.iter().collect();
+
+    let composition = testing::composition(vec![
+        testing::text_atom("the"),
+        testing::text_atom("cat"),
+        testing::text_atom("ran"),
+    ]);
+
+    let mut group = c.benchmark_group("long_sentence");
+    group.bench_function("apply", |b| {
+        b.iter(|| {
+            (0..refs.len())
+                .filter(|&start| composition.apply(&refs, start).is_some())
+                .count()
+        })
+    });
+    group.bench_function("apply_into", |b| {
+        let mut graph = MatchGraph::default();
+
+        b.iter(|| {
+            (0..refs.len())
+                .filter(|&start| composition.apply_into(&refs, start, &mut graph))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, apply);
+criterion_main!(benches);
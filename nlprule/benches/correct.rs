@@ -0,0 +1,72 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use nlprule::{
+    rules::{apply_suggestions, correct_in_place},
+    testing,
+    types::Suggestion,
+    Rules,
+};
+
+const TEXTS: [&str; 4] = [
+    "Teh cat sat on teh mat.",
+    "I saw teh dog yesterday.",
+    "This is a nice dya.",
+    "Ths dya was teh best.",
+];
+
+/// Short texts together with the suggestions of rules fixing typos. If `same_length` is true,
+/// all replacements have the same length as the replaced text, otherwise some are longer.
+fn batch(same_length: bool) -> Vec<(String, Vec<Suggestion>)> {
+    let tokenizer = testing::tokenizer(&[]);
+
+    let mut rules = Rules::default();
+    rules.push(testing::rule("TEH", "teh", "the"));
+    rules.push(testing::rule("DYA", "dya", "day"));
+    if !same_length {
+        rules.push(testing::rule("THS", "Ths", "This"));
+    }
+
+    let batch: Vec<_> = TEXTS
+        .iter()
+        .cycle()
+        .take(100)
+        .map(|text| (text.to_string(), rules.suggest(text, &tokenizer)))
+        .collect();
+    assert!(batch.iter().all(|(_, suggestions)| !suggestions.is_empty()));
+
+    batch
+}
+
+fn correct(c: &mut Criterion) {
+    for &(name, same_length) in &[("same_length", true), ("different_length", false)] {
+        let batch = batch(same_length);
+        let mut group = c.benchmark_group(name);
+
+        group.bench_function("apply_suggestions", |b| {
+            b.iter(|| {
+                batch
+                    .iter()
+                    .map(|(text, suggestions)| apply_suggestions(text, suggestions).len())
+                    .sum::<usize>()
+            })
+        });
+        group.bench_function("correct_in_place", |b| {
+            let mut buffer = String::new();
+
+            b.iter(|| {
+                batch
+                    .iter()
+                    .map(|(text, suggestions)| {
+                        buffer.clear();
+                        buffer.push_str(text);
+                        correct_in_place(&mut buffer, suggestions).unwrap();
+                        buffer.len()
+                    })
+                    .sum::<usize>()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, correct);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use nlprule::{testing, Rules};
+
+/// Rules fixing typos which all have trigger words. If `pos_rule` is true, a rule matching on a
+/// part-of-speech tag only is added, so the rules can not be skipped based on the trigger words.
+fn rules(pos_rule: bool) -> Rules {
+    let mut rules = Rules::default();
+    for i in 0..200 {
+        rules.push(testing::rule(
+            &format!("TYPO{}", i),
+            &format!("typo{}", i),
+            "fixed",
+        ));
+    }
+    if pos_rule {
+        rules.push(testing::atom_rule(
+            "POS",
+            vec![testing::pos_set_atom(&["UH"])],
+            "",
+        ));
+    }
+    rules
+}
+
+fn clean_input(c: &mut Criterion) {
+    let tokenizer = testing::tokenizer(&[]);
+    let text =
+        "The quick brown fox jumps over the lazy dog. It was a sunny day and nothing was wrong. "
+            .repeat(5);
+
+    let mut group = c.benchmark_group("clean_input");
+    for &(name, pos_rule) in &[("full_pass", true), ("early_return", false)] {
+        let rules = rules(pos_rule);
+        assert!(rules.suggest(&text, &tokenizer).is_empty());
+
+        group.bench_function(name, |b| b.iter(|| rules.suggest(&text, &tokenizer)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, clean_input);
+criterion_main!(benches);
@@ -0,0 +1,76 @@
+//! A self-contained grammar checker bundling [Rules] with the [Tokenizer] they were built for.
+//! Useful where passing both around is inconvenient, e. g. to expose nlprule to JavaScript via WebAssembly.
+//! Oniguruma does not compile to WebAssembly, so the crate has to be built with `default-features = false`
+//! and the `regex-pure` and `json` features instead:
+//!
+//! ```ignore
+//! use wasm_bindgen::prelude::*;
+//!
+//! #[wasm_bindgen]
+//! pub struct JsChecker(nlprule::Checker);
+//!
+//! #[wasm_bindgen]
+//! impl JsChecker {
+//!     #[wasm_bindgen(constructor)]
+//!     pub fn new(tokenizer: &[u8], rules: &[u8]) -> Result<JsChecker, JsValue> {
+//!         nlprule::Checker::from_bytes(tokenizer, rules)
+//!             .map(JsChecker)
+//!             .map_err(|x| JsValue::from_str(&x.to_string()))
+//!     }
+//!
+//!     pub fn suggest(&self, text: &str) -> String {
+//!         self.0.suggest_json_str(text)
+//!     }
+//! }
+//! ```
+
+use crate::{types::Suggestion, Rules, Tokenizer};
+
+/// Owns a set of [Rules] together with the [Tokenizer] they were built for, so no references
+/// have to be kept by the caller.
+pub struct Checker {
+    tokenizer: Tokenizer,
+    rules: Rules,
+}
+
+impl Checker {
+    /// Creates a new checker from a tokenizer and rules for the same language.
+    pub fn new(tokenizer: Tokenizer, rules: Rules) -> Self {
+        Checker { tokenizer, rules }
+    }
+
+    /// Creates a new checker from the bytes of a tokenizer and a rules binary, e. g. fetched by a browser.
+    pub fn from_bytes(tokenizer: &[u8], rules: &[u8]) -> bincode::Result<Self> {
+        Ok(Checker {
+            tokenizer: Tokenizer::new_from(tokenizer)?,
+            rules: Rules::new_from(rules)?,
+        })
+    }
+
+    /// Gets the tokenizer used to split texts into sentences and tokens.
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    /// Gets the rules which are applied to the tokenized texts.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Compute the suggestions for a text, see [suggest][Rules::suggest].
+    pub fn suggest(&self, text: &str) -> Vec<Suggestion> {
+        self.rules.suggest(text, &self.tokenizer)
+    }
+
+    /// Correct a text by applying the first replacement of each suggestion, see [correct][Rules::correct].
+    pub fn correct(&self, text: &str) -> String {
+        self.rules.correct(text, &self.tokenizer)
+    }
+
+    /// Compute the suggestions for a text and serialize them to a JSON array of [SuggestionView][crate::types::SuggestionView]s,
+    /// see [suggest_json][Rules::suggest_json]. Only available with the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn suggest_json_str(&self, text: &str) -> String {
+        self.rules.suggest_json(text, &self.tokenizer)
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
     utils::parallelism::MaybeParallelIterator,
 };
 
-use super::parse_structure::BuildInfo;
+use super::{parse_structure::BuildInfo, CompileError};
 
 impl TextMatcher {
     pub fn new(matcher: Matcher, info: &mut BuildInfo) -> Self {
@@ -27,6 +27,7 @@ impl TextMatcher {
             regex.hash(&mut hasher);
             matcher.negate.hash(&mut hasher);
             matcher.empty_always_false.hash(&mut hasher);
+            matcher.empty_match.hash(&mut hasher);
             let matcher_hash = hasher.finish();
 
             if let Some(set) = info.mut_regex_cache().get(&matcher_hash) {
@@ -79,15 +80,73 @@ impl Rules {
         build_info: &mut BuildInfo,
         options: RulesOptions,
     ) -> Self {
+        Rules::from_xml_with_progress(path, build_info, options, |_, _| {})
+    }
+
+    /// Like [from_xml][Rules::from_xml], but calls `progress` with the number of processed rules
+    /// and the total number of rules after each rule, e. g. to display a progress bar.
+    pub fn from_xml_with_progress<P, F>(
+        path: P,
+        build_info: &mut BuildInfo,
+        options: RulesOptions,
+        progress: F,
+    ) -> Self
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(usize, usize),
+    {
+        Rules::from_xml_with_errors_and_progress(path, build_info, options, progress).0
+    }
+
+    /// Like [from_xml][Rules::from_xml], but additionally returns the errors of rules which could not be compiled
+    /// so they can be inspected programmatically. Rules with errors are skipped as in [from_xml][Rules::from_xml].
+    pub fn from_xml_with_errors<P: AsRef<std::path::Path>>(
+        path: P,
+        build_info: &mut BuildInfo,
+        options: RulesOptions,
+    ) -> (Self, Vec<CompileError>) {
+        Rules::from_xml_with_errors_and_progress(path, build_info, options, |_, _| {})
+    }
+
+    /// Fallible version of [from_xml][Rules::from_xml]. If `options.allow_errors` is `false`, returns the first error
+    /// of a rule which could not be compiled. Otherwise, returns the rules together with the errors of all skipped rules
+    /// as warnings. Unlike [from_xml][Rules::from_xml], this does not rely on logging to surface broken rules e. g. in CI.
+    pub fn try_from_xml<P: AsRef<std::path::Path>>(
+        path: P,
+        build_info: &mut BuildInfo,
+        options: RulesOptions,
+    ) -> Result<(Self, Vec<CompileError>), CompileError> {
+        let allow_errors = options.allow_errors;
+        let (rules, mut errors) = Rules::from_xml_with_errors(path, build_info, options);
+
+        if !allow_errors && !errors.is_empty() {
+            return Err(errors.remove(0));
+        }
+
+        Ok((rules, errors))
+    }
+
+    fn from_xml_with_errors_and_progress<P, F>(
+        path: P,
+        build_info: &mut BuildInfo,
+        options: RulesOptions,
+        mut progress: F,
+    ) -> (Self, Vec<CompileError>)
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(usize, usize),
+    {
         use log::warn;
         use std::collections::HashMap;
 
         let rules = super::parse_structure::read_rules(path);
-        let mut errors: HashMap<String, usize> = HashMap::new();
+        let n_rules = rules.len();
+        build_info.set_force_case_insensitive(options.case_insensitive);
+        let mut errors: Vec<CompileError> = Vec::new();
 
         let rules: Vec<_> = rules
             .into_iter()
-            .filter_map(|x| match x {
+            .map(|x| match x {
                 Ok((rule_structure, group, category)) => {
                     let id = rule_structure.id.as_ref().map_or_else(
                         || {
@@ -96,6 +155,13 @@ impl Rules {
                         },
                         |x| x.clone(),
                     );
+                    // filter before building the rule so excluded rules skip compilation entirely
+                    if (!options.ids.is_empty() && !options.ids.contains(&id))
+                        || options.ignore_ids.contains(&id)
+                    {
+                        return None;
+                    }
+
                     let category = category.expect("grammar rules must have category");
                     let off = rule_structure
                         .default
@@ -118,41 +184,65 @@ impl Rules {
 
                     match Rule::from_rule_structure(rule_structure, build_info) {
                         Ok(mut rule) => {
-                            if (options.ids.is_empty() || options.ids.contains(&id))
-                                && !options.ignore_ids.contains(&id)
-                            {
-                                rule.id = id;
-                                rule.name = name;
-                                rule.on = !off;
-                                rule.category_id = category.id;
-                                rule.category_name = category.name;
-                                rule.category_type = category.kind;
-                                Some(rule)
-                            } else {
-                                None
-                            }
+                            rule.id = id;
+                            rule.name = name;
+                            rule.on = !off;
+                            rule.category_id = category.id;
+                            rule.category_name = category.name;
+                            rule.category_type = category.kind;
+                            Some(rule)
                         }
                         Err(x) => {
-                            *errors.entry(format!("[Rule] {}", x)).or_insert(0) += 1;
+                            errors.push(CompileError::from_error(id, x));
                             None
                         }
                     }
                 }
                 Err(x) => {
-                    *errors.entry(format!("[Structure] {}", x)).or_insert(0) += 1;
+                    errors.push(CompileError::StructureError {
+                        id: None,
+                        message: format!("{}", x),
+                    });
                     None
                 }
             })
+            .enumerate()
+            // report progress once a rule has been compiled (or skipped), not before
+            .inspect(|(i, _)| progress(*i + 1, n_rules))
+            .filter_map(|(_, x)| x)
             .collect();
 
         if !errors.is_empty() {
-            let mut errors: Vec<(String, usize)> = errors.into_iter().collect();
-            errors.sort_by_key(|x| -(x.1 as i32));
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            for error in &errors {
+                *counts.entry(error.to_string()).or_insert(0) += 1;
+            }
+
+            let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+            warn!("Errors constructing Rules: {:#?}", &counts);
+        }
+
+        build_info.set_force_case_insensitive(false);
 
-            warn!("Errors constructing Rules: {:#?}", &errors);
+        let interner = build_info.regex_interner();
+        if interner.n_deduplicated() > 0 {
+            warn!(
+                "Deduplicated {} regexes, {} distinct regexes compiled.",
+                interner.n_deduplicated(),
+                interner.n_compiled()
+            );
         }
 
-        Rules { rules }
+        (
+            Rules {
+                rules,
+                post_processors: Vec::new(),
+                synonym_provider: None,
+            },
+            errors,
+        )
     }
 }
 
@@ -180,12 +270,15 @@ impl Tokenizer {
                         |x| x.clone(),
                     );
 
+                    if (!options.ids.is_empty() && !options.ids.contains(&id))
+                        || options.ignore_ids.contains(&id)
+                    {
+                        return None;
+                    }
+
                     match DisambiguationRule::from_rule_structure(rule_structure, build_info) {
                         Ok(mut rule) => {
-                            if error.is_none()
-                                && (options.ids.is_empty() || options.ids.contains(&id))
-                                && !options.ignore_ids.contains(&id)
-                            {
+                            if error.is_none() {
                                 rule.id = id;
 
                                 Some(rule)
@@ -278,11 +371,19 @@ mod composition {
     use super::*;
     use crate::{
         rule::engine::composition::{
-            AndAtom, Atom, Composition, FalseAtom, NotAtom, OffsetAtom, OrAtom, Part, Quantifier,
-            TrueAtom,
+            concrete::{
+                AbbreviationAtom, ByteAtom, CaseAtom, ChunkSetAtom, ChunkSpanAtom, DateAtom,
+                LengthAtom, NumberWordAtom,
+            },
+            AndAtom, Atom, ByteMatcher, Casing, Composition, DictionaryMatcher, FalseAtom,
+            GroupEqualityAtom, GroupFeatureAtom, LookaheadAtom, LookbehindAtom, MatchGraph,
+            NotAtom, NumberWordMatcher, NumericComparison, OffsetAtom, OrAtom, Part, PositionAtom,
+            PositionPredicate, PredicateAtom, Quantifier, SetMatcher, TrueAtom, WithinGroupAtom,
         },
-        utils::regex::SerializeRegex,
+        utils::{lang::Language, regex::SerializeRegex},
+        Error,
     };
+    use std::sync::Arc;
 
     impl Matcher {
         pub fn new_regex(regex: SerializeRegex, negate: bool, empty_always_false: bool) -> Self {
@@ -291,9 +392,36 @@ mod composition {
                 negate,
                 case_sensitive: true, // handled by regex
                 empty_always_false,
+                empty_match: None,
+            }
+        }
+
+        /// Creates a regex matcher where the result for empty input is set explicitly
+        /// instead of depending on `negate` (see the table at [Matcher]).
+        pub fn new_regex_full(regex: SerializeRegex, negate: bool, empty_match: bool) -> Self {
+            Matcher {
+                matcher: either::Right(regex),
+                negate,
+                case_sensitive: true, // handled by regex
+                empty_always_false: !empty_match,
+                empty_match: Some(empty_match),
             }
         }
 
+        /// Creates a matcher from a glob pattern e. g. `un*able`.
+        /// Supports `*` (any number of chars), `?` (exactly one char) and character classes like `[a-z]` or `[!aeiou]`.
+        /// The pattern always has to match the whole input.
+        pub fn new_glob(
+            pattern: &str,
+            negate: bool,
+            case_sensitive: bool,
+            empty_always_false: bool,
+        ) -> Result<Self, Error> {
+            let regex = SerializeRegex::new(&glob_to_regex(pattern)?, true, case_sensitive)?;
+
+            Ok(Matcher::new_regex(regex, negate, empty_always_false))
+        }
+
         pub fn new_string(
             string_or_idx: either::Either<String, usize>,
             negate: bool,
@@ -305,6 +433,7 @@ mod composition {
                 negate,
                 case_sensitive,
                 empty_always_false,
+                empty_match: None,
             }
         }
 
@@ -313,6 +442,62 @@ mod composition {
         }
     }
 
+    pub(super) fn glob_to_regex(pattern: &str) -> Result<String, Error> {
+        let mut regex = String::new();
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                '[' => {
+                    let mut class = String::from("[");
+                    let mut closed = false;
+
+                    if let Some(next) = chars.next() {
+                        match next {
+                            '!' | '^' => class.push('^'),
+                            ']' => class.push_str("\\]"),
+                            x => class.push(x),
+                        }
+                    }
+
+                    for c in &mut chars {
+                        match c {
+                            ']' => {
+                                closed = true;
+                                break;
+                            }
+                            '\\' | '[' => {
+                                class.push('\\');
+                                class.push(c);
+                            }
+                            x => class.push(x),
+                        }
+                    }
+
+                    if !closed {
+                        return Err(Error::Unexpected(format!(
+                            "unclosed character class in glob {}",
+                            pattern
+                        )));
+                    }
+
+                    class.push(']');
+                    regex.push_str(&class);
+                }
+                x => {
+                    if r".^$|()[]{}+\\".contains(x) {
+                        regex.push('\\');
+                    }
+                    regex.push(x);
+                }
+            }
+        }
+
+        Ok(regex)
+    }
+
     impl Quantifier {
         pub fn new(min: usize, max: usize) -> Self {
             assert!(max >= min);
@@ -337,6 +522,27 @@ mod composition {
         }
     }
 
+    /// Gets the chunk this atom matches if it only matches one literal, case-sensitive chunk.
+    fn chunk_literal(atom: &Atom) -> Option<&str> {
+        match atom {
+            Atom::ChunkAtom(atom) => atom
+                .matcher
+                .literal()
+                .filter(|x| !x.is_empty() && atom.matcher.case_sensitive),
+            // the same chunk condition can occur multiple times in one `AndAtom`
+            Atom::AndAtom(atom) => {
+                let first = chunk_literal(atom.atoms.first()?)?;
+
+                if atom.atoms.iter().all(|x| chunk_literal(x) == Some(first)) {
+                    Some(first)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
     impl OrAtom {
         pub fn or(atoms: Vec<Atom>) -> Atom {
             let mut atoms: Vec<_> = atoms
@@ -348,6 +554,15 @@ mod composition {
                 (FalseAtom {}).into()
             } else if atoms.len() == 1 {
                 atoms.remove(0)
+            } else if let Some(set) = atoms
+                .iter()
+                .map(|x| chunk_literal(x).map(|x| x.to_string()))
+                .collect::<Option<DefaultHashSet<_>>>()
+            {
+                (ChunkSetAtom {
+                    matcher: SetMatcher { set, negate: false },
+                })
+                .into()
             } else {
                 (OrAtom { atoms }).into()
             }
@@ -356,9 +571,22 @@ mod composition {
 
     impl NotAtom {
         pub fn not(atom: Atom) -> Atom {
+            if let Some(chunk) = chunk_literal(&atom) {
+                let set = std::iter::once(chunk.to_string()).collect();
+
+                return (ChunkSetAtom {
+                    matcher: SetMatcher { set, negate: true },
+                })
+                .into();
+            }
+
             match atom {
                 Atom::TrueAtom { .. } => FalseAtom::default().into(),
                 Atom::FalseAtom { .. } => TrueAtom::default().into(),
+                Atom::ChunkSetAtom(mut atom) => {
+                    atom.matcher.negate = !atom.matcher.negate;
+                    atom.into()
+                }
                 x => (NotAtom { atom: Box::new(x) }).into(),
             }
         }
@@ -373,8 +601,185 @@ mod composition {
         }
     }
 
+    impl PositionAtom {
+        /// Matches only the token at index `n`.
+        pub fn at(n: usize) -> Self {
+            PositionAtom {
+                predicate: PositionPredicate::Equals(n),
+            }
+        }
+
+        /// Matches every `k`-th token, starting at index zero.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `k` is zero.
+        pub fn every(k: usize) -> Self {
+            assert!(k > 0);
+            PositionAtom {
+                predicate: PositionPredicate::Every(k),
+            }
+        }
+
+        /// Matches all tokens with an index greater than `n`.
+        pub fn after(n: usize) -> Self {
+            PositionAtom {
+                predicate: PositionPredicate::GreaterThan(n),
+            }
+        }
+    }
+
+    impl LengthAtom {
+        /// Matches tokens with more than `n` chars.
+        pub fn longer_than(n: usize) -> Self {
+            LengthAtom {
+                min: n + 1,
+                max: usize::MAX,
+            }
+        }
+    }
+
+    impl DictionaryMatcher {
+        /// Loads a dictionary from a file with one word per line. Empty lines are skipped.
+        pub fn from_file<P: AsRef<std::path::Path>>(
+            path: P,
+            case_sensitive: bool,
+        ) -> std::io::Result<Self> {
+            use std::io::BufRead;
+
+            let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+            let mut words = DefaultHashSet::default();
+
+            for line in reader.lines() {
+                let line = line?;
+                let word = line.trim();
+
+                if !word.is_empty() {
+                    words.insert(if case_sensitive {
+                        word.to_string()
+                    } else {
+                        word.to_lowercase()
+                    });
+                }
+            }
+
+            Ok(DictionaryMatcher {
+                words: Arc::new(words),
+                case_sensitive,
+            })
+        }
+    }
+
+    impl AbbreviationAtom {
+        /// Matches the abbreviations of the tokenizer, see [Tokenizer::abbreviations].
+        pub fn new(tokenizer: &Tokenizer) -> Self {
+            AbbreviationAtom {
+                abbreviations: Arc::new(tokenizer.abbreviations().iter().cloned().collect()),
+            }
+        }
+    }
+
+    impl NumberWordAtom {
+        pub fn new(language: Language, comparison: Option<NumericComparison>) -> Self {
+            NumberWordAtom {
+                matcher: NumberWordMatcher {
+                    language,
+                    comparison,
+                },
+            }
+        }
+    }
+
+    impl DateAtom {
+        pub fn new(language: Language, valid: Option<bool>) -> Self {
+            DateAtom { language, valid }
+        }
+    }
+
+    impl ByteAtom {
+        pub fn new(matcher: ByteMatcher) -> Self {
+            ByteAtom { matcher }
+        }
+    }
+
+    impl CaseAtom {
+        pub fn new(casing: Casing) -> Self {
+            CaseAtom { casing }
+        }
+    }
+
+    impl ChunkSpanAtom {
+        /// Matches a full phrase with the given chunk e. g. "NP". The group is set by [Composition::new].
+        pub fn new(chunk: &str) -> Self {
+            ChunkSpanAtom {
+                chunk: chunk.to_string(),
+                group: 0,
+            }
+        }
+    }
+
+    impl GroupEqualityAtom {
+        pub fn new(a: usize, b: usize) -> Self {
+            GroupEqualityAtom { a, b }
+        }
+    }
+
+    impl GroupFeatureAtom {
+        pub fn new(group: usize, feature: &str) -> Self {
+            GroupFeatureAtom {
+                group,
+                feature: feature.to_string(),
+            }
+        }
+    }
+
+    impl PredicateAtom {
+        pub fn new<F>(predicate: F) -> Self
+        where
+            F: Fn(&Token, &MatchGraph) -> bool + Send + Sync + 'static,
+        {
+            PredicateAtom {
+                predicate: Box::new(predicate),
+            }
+        }
+    }
+
+    impl WithinGroupAtom {
+        pub fn new(group: usize) -> Self {
+            WithinGroupAtom { group }
+        }
+    }
+
+    impl LookaheadAtom {
+        pub fn new(atom: Atom, min: usize, max: usize) -> Self {
+            assert!(max >= min && min > 0);
+            LookaheadAtom {
+                atom: Box::new(atom),
+                min,
+                max,
+            }
+        }
+    }
+
+    impl LookbehindAtom {
+        pub fn new(atom: Atom, min: usize, max: usize) -> Self {
+            assert!(max >= min && min > 0);
+            LookbehindAtom {
+                atom: Box::new(atom),
+                min,
+                max,
+            }
+        }
+    }
+
     impl Composition {
-        pub fn new(parts: Vec<Part>) -> Self {
+        pub fn new(mut parts: Vec<Part>) -> Self {
+            for (i, part) in parts.iter_mut().enumerate() {
+                if let Atom::ChunkSpanAtom(atom) = &mut part.atom {
+                    atom.group = i + 1;
+                }
+            }
+
             let mut group_ids_to_idx = DefaultHashMap::default();
             group_ids_to_idx.insert(0, 0);
             let mut current_id = 1;
@@ -398,3 +803,180 @@ mod composition {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::composition::glob_to_regex;
+
+    #[test]
+    fn glob_converts_to_anchorable_regex() {
+        assert_eq!(glob_to_regex("un*able").unwrap(), "un.*able");
+        assert_eq!(glob_to_regex("b?t").unwrap(), "b.t");
+        assert_eq!(glob_to_regex("[!aeiou]x").unwrap(), "[^aeiou]x");
+        assert_eq!(glob_to_regex("a.b(c)").unwrap(), r"a\.b\(c\)");
+        assert!(glob_to_regex("[abc").is_err());
+    }
+
+    #[test]
+    fn regex_full_controls_empty_input() {
+        use crate::{
+            rule::{engine::composition::Matcher, MatchGraph},
+            utils::regex::SerializeRegex,
+        };
+
+        let graph = MatchGraph::default();
+        let regex = || SerializeRegex::new("a+", true, true).unwrap();
+
+        for &negate in &[false, true] {
+            for &empty_match in &[false, true] {
+                let matcher = Matcher::new_regex_full(regex(), negate, empty_match);
+
+                assert_eq!(matcher.is_match("", &graph, None), empty_match);
+                assert_eq!(matcher.is_match("aa", &graph, None), !negate);
+            }
+        }
+    }
+
+    #[test]
+    fn excluded_rules_are_not_compiled() {
+        use crate::{
+            compile::{
+                parse_structure::{BuildInfo, RegexCache},
+                CompileError,
+            },
+            rules::RulesOptions,
+            utils::testing,
+            Rules,
+        };
+        use std::{io::Write, sync::Arc};
+
+        let path =
+            std::env::temp_dir().join(format!("nlprule_excluded_rules_{}.xml", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        write!(
+            file,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rules lang="en">
+    <category id="TEST" name="Test">
+        <rule id="GOOD" name="Good">
+            <pattern><token>teh</token></pattern>
+            <message>Did you mean <suggestion>the</suggestion>?</message>
+            <example correction="the"><marker>teh</marker></example>
+        </rule>
+        <rule id="BROKEN" name="Broken">
+            <pattern><token regexp="yes">(unclosed</token></pattern>
+            <message>Broken.</message>
+            <example>unclosed</example>
+        </rule>
+    </category>
+</rules>"#
+        )
+        .unwrap();
+        drop(file);
+
+        let compile = |options: RulesOptions| {
+            let mut build_info = BuildInfo::new(Arc::new(testing::tagger(&[])), RegexCache::new(0));
+            let (rules, errors) = Rules::from_xml_with_errors(&path, &mut build_info, options);
+
+            (
+                rules
+                    .rules()
+                    .iter()
+                    .map(|x| x.id().to_string())
+                    .collect::<Vec<_>>(),
+                errors,
+            )
+        };
+
+        let (ids, errors) = compile(RulesOptions::default());
+        assert_eq!(ids, vec!["GOOD"]);
+        assert!(matches!(
+            errors.as_slice(),
+            [CompileError::RegexError { id: Some(id), .. }] if id == "BROKEN"
+        ));
+
+        for options in &[
+            RulesOptions {
+                ignore_ids: vec!["BROKEN".into()],
+                ..RulesOptions::default()
+            },
+            RulesOptions {
+                ids: vec!["GOOD".into()],
+                ..RulesOptions::default()
+            },
+        ] {
+            let (ids, errors) = compile(options.clone());
+            assert_eq!(ids, vec!["GOOD"]);
+            assert!(errors.is_empty());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn confidence_outside_unit_interval_is_rejected() {
+        use crate::{
+            compile::{
+                parse_structure::{BuildInfo, RegexCache},
+                CompileError,
+            },
+            utils::testing,
+            Rules,
+        };
+        use std::{io::Write, sync::Arc};
+
+        let path =
+            std::env::temp_dir().join(format!("nlprule_confidence_{}.xml", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(
+            file,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rules lang="en">
+    <category id="TEST" name="Test">"#
+        )
+        .unwrap();
+        for (id, confidence) in &[
+            ("HALF", "0.5"),
+            ("ABOVE", "1.5"),
+            ("NEGATIVE", "-0.1"),
+            ("NAN", "NaN"),
+        ] {
+            writeln!(
+                file,
+                r#"        <rule id="{}" name="{}" confidence="{}">
+            <pattern><token>teh</token></pattern>
+            <message>Did you mean <suggestion>the</suggestion>?</message>
+            <example correction="the"><marker>teh</marker></example>
+        </rule>"#,
+                id, id, confidence
+            )
+            .unwrap();
+        }
+        writeln!(file, "    </category>\n</rules>").unwrap();
+        drop(file);
+
+        let mut build_info = BuildInfo::new(Arc::new(testing::tagger(&[])), RegexCache::new(0));
+        let (rules, errors) =
+            Rules::from_xml_with_errors(&path, &mut build_info, Default::default());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            rules
+                .rules()
+                .iter()
+                .map(|x| (x.id(), x.confidence()))
+                .collect::<Vec<_>>(),
+            vec![("HALF", Some(0.5))]
+        );
+        assert_eq!(
+            errors
+                .iter()
+                .map(|x| match x {
+                    CompileError::StructureError { id: Some(id), .. } => id.as_str(),
+                    _ => panic!("unexpected error {:?}", x),
+                })
+                .collect::<Vec<_>>(),
+            vec!["ABOVE", "NEGATIVE", "NAN"]
+        );
+    }
+}
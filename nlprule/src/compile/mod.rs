@@ -7,11 +7,13 @@ use std::{
 };
 
 use clap::Clap;
+use thiserror::Error;
 
 use crate::{
     rules::{Rules, RulesOptions},
     tokenizer::{chunk::Chunker, tag::Tagger, Tokenizer, TokenizerOptions},
     types::DefaultHasher,
+    Error as NlpruleError,
 };
 
 use self::parse_structure::{BuildInfo, RegexCache};
@@ -20,6 +22,41 @@ mod impls;
 mod parse_structure;
 mod structure;
 
+/// An error which occured while compiling a single rule.
+/// The ID is the ID of the rule if it could be determined.
+#[derive(Error, Debug)]
+pub enum CompileError {
+    #[error("[Regex] {message}")]
+    RegexError { id: Option<String>, message: String },
+    #[error("[Structure] {message}")]
+    StructureError { id: Option<String>, message: String },
+    #[error("[Unsupported] {message}")]
+    UnsupportedFeature { id: Option<String>, message: String },
+}
+
+impl CompileError {
+    pub(crate) fn from_error(id: String, error: NlpruleError) -> Self {
+        let id = Some(id);
+
+        match error {
+            NlpruleError::Regex(message) => CompileError::RegexError { id, message },
+            NlpruleError::Unimplemented(message) => {
+                CompileError::UnsupportedFeature { id, message }
+            }
+            NlpruleError::Unexpected(message) => CompileError::StructureError { id, message },
+        }
+    }
+
+    /// Gets the ID of the rule this error occured in if it is known.
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            CompileError::RegexError { id, .. }
+            | CompileError::StructureError { id, .. }
+            | CompileError::UnsupportedFeature { id, .. } => id.as_deref(),
+        }
+    }
+}
+
 #[derive(Clap)]
 #[clap(
     version = env!("CARGO_PKG_VERSION"),
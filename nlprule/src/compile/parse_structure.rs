@@ -21,6 +21,8 @@ fn max_matches() -> usize {
     20
 }
 
+/// Caches the sets of vocabulary words matching a regex (see [TextMatcher][crate::rule::engine::composition::TextMatcher])
+/// between builds. Only valid for the word store with the hash `word_hash`.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RegexCache {
     cache: DefaultHashMap<u64, Option<DefaultHashSet<u32>>>,
@@ -53,9 +55,50 @@ impl RegexCache {
     }
 }
 
+/// Interns compiled regexes so that identical patterns share one compiled [Regex].
+#[derive(Default)]
+pub struct RegexInterner {
+    regexes: DefaultHashMap<(String, bool), Arc<Regex>>,
+    n_requested: usize,
+}
+
+impl RegexInterner {
+    pub fn get(
+        &mut self,
+        regex_str: &str,
+        must_fully_match: bool,
+        case_sensitive: bool,
+    ) -> Result<SerializeRegex, Error> {
+        let (fixed, case_sensitive) =
+            SerializeRegex::fix(regex_str, must_fully_match, case_sensitive);
+        let key = (fixed, case_sensitive);
+        self.n_requested += 1;
+
+        if let Some(compiled) = self.regexes.get(&key) {
+            return SerializeRegex::from_fixed(key.0, key.1, Some(compiled.clone()));
+        }
+
+        let regex = SerializeRegex::from_fixed(key.0.clone(), key.1, None)?;
+        self.regexes.insert(key, regex.compiled().clone());
+        Ok(regex)
+    }
+
+    /// The number of regexes which were requested but did not have to be compiled because an identical one already existed.
+    pub fn n_deduplicated(&self) -> usize {
+        self.n_requested - self.regexes.len()
+    }
+
+    /// The number of distinct compiled regexes.
+    pub fn n_compiled(&self) -> usize {
+        self.regexes.len()
+    }
+}
+
 pub struct BuildInfo {
     tagger: Arc<Tagger>,
     regex_cache: RegexCache,
+    regex_interner: RegexInterner,
+    force_case_insensitive: bool,
 }
 
 impl BuildInfo {
@@ -63,9 +106,31 @@ impl BuildInfo {
         BuildInfo {
             tagger,
             regex_cache,
+            regex_interner: RegexInterner::default(),
+            force_case_insensitive: false,
         }
     }
 
+    /// Sets whether all text matchers built from now on are case-insensitive regardless of the rule attributes.
+    pub(crate) fn set_force_case_insensitive(&mut self, force_case_insensitive: bool) {
+        self.force_case_insensitive = force_case_insensitive;
+    }
+
+    /// Creates a new regex, sharing the compiled regex with previously created regexes with the same pattern.
+    pub fn regex(
+        &mut self,
+        regex_str: &str,
+        must_fully_match: bool,
+        case_sensitive: bool,
+    ) -> Result<SerializeRegex, Error> {
+        self.regex_interner
+            .get(regex_str, must_fully_match, case_sensitive)
+    }
+
+    pub fn regex_interner(&self) -> &RegexInterner {
+        &self.regex_interner
+    }
+
     pub fn tagger(&self) -> &Arc<Tagger> {
         &self.tagger
     }
@@ -92,7 +157,7 @@ fn parse_match_attribs(
         }
     } else {
         case_sensitive
-    };
+    } && !info.force_case_insensitive;
 
     let inflected = if let Some(inflected) = attribs.inflected() {
         match inflected.as_str() {
@@ -137,7 +202,7 @@ fn parse_match_attribs(
 
     if text.is_some() || text_match_idx.is_some() {
         let matcher = if is_regex && text_match_idx.is_none() {
-            let regex = SerializeRegex::new(text.unwrap().trim(), true, case_sensitive);
+            let regex = info.regex(text.unwrap().trim(), true, case_sensitive);
             Matcher::new_regex(regex?, negate, inflected)
         } else {
             Matcher::new_string(
@@ -165,7 +230,7 @@ fn parse_match_attribs(
 
     if let Some(postag) = attribs.postag() {
         let raw_matcher = if is_postag_regexp {
-            let regex = SerializeRegex::new(&postag.trim(), true, true);
+            let regex = info.regex(&postag.trim(), true, true);
             Matcher::new_regex(regex?, negate_pos, true)
         } else {
             Matcher::new_string(
@@ -205,7 +270,7 @@ fn parse_match_attribs(
             atoms.push(chunk_atom.into());
         }
         (None, Some(chunk_re)) => {
-            let regex = SerializeRegex::new(chunk_re.trim(), true, true)?;
+            let regex = info.regex(chunk_re.trim(), true, true)?;
             let chunk_atom = ChunkAtom {
                 matcher: Matcher::new_regex(regex, false, true),
             };
@@ -414,7 +479,7 @@ fn parse_match(
 
         let matcher = match m.postag_regex.as_deref() {
             Some("yes") => {
-                let regex = SerializeRegex::new(&postag, true, false)?;
+                let regex = info.regex(&postag, true, false)?;
                 Matcher::new_regex(regex, false, true)
             }
             None => Matcher::new_string(either::Left(postag), false, false, true),
@@ -428,10 +493,9 @@ fn parse_match(
     };
 
     let regex_replacer = match (m.regexp_match, m.regexp_replace) {
-        (Some(regex_match), Some(regex_replace)) => Some((
-            SerializeRegex::new(&regex_match, false, true)?,
-            regex_replace,
-        )),
+        (Some(regex_match), Some(regex_replace)) => {
+            Some((info.regex(&regex_match, false, true)?, regex_replace))
+        }
         _ => None,
     };
 
@@ -496,6 +560,18 @@ fn parse_suggestion(
     composition: &Option<&Composition>,
     info: &mut BuildInfo,
 ) -> Result<Synthesizer, Error> {
+    // `case_conversion="sentence"` capitalizes the suggestion iff it starts a sentence
+    let sentence_case = match data.case_conversion.as_deref() {
+        Some("sentence") => true,
+        Some(x) => {
+            return Err(Error::Unimplemented(format!(
+                "suggestion case conversion {} not supported.",
+                x
+            )))
+        }
+        None => false,
+    };
+
     let mut parts = Vec::new();
     for part in data.parts {
         match part {
@@ -512,6 +588,7 @@ fn parse_suggestion(
         parts,
         // use titlecase adjustment (i. e. make replacement title case if match is title case) if token rule
         use_titlecase_adjust: composition.is_some(),
+        sentence_case,
     })
 }
 
@@ -681,6 +758,39 @@ impl Rule {
             ));
         }
 
+        let first_match_only = match data.first_match_only.as_deref() {
+            Some("yes") => true,
+            Some("no") | None => false,
+            Some(x) => {
+                return Err(Error::Unexpected(format!(
+                    "unknown first_match_only value {:?}",
+                    x
+                )))
+            }
+        };
+
+        let confidence = data
+            .confidence
+            .as_deref()
+            .map(|x| match x.parse::<f32>() {
+                Ok(confidence) if (0.0..=1.0).contains(&confidence) => Ok(confidence),
+                _ => Err(Error::Unexpected(format!(
+                    "invalid confidence value {:?}, must be between zero and one",
+                    x
+                ))),
+            })
+            .transpose()?;
+
+        let priority = data
+            .priority
+            .as_deref()
+            .map(|x| {
+                x.parse::<i32>()
+                    .map_err(|_| Error::Unexpected(format!("invalid priority value {:?}", x)))
+            })
+            .transpose()?
+            .unwrap_or(0);
+
         let (engine, start, end) = match (data.pattern, data.regex) {
             (Some(_), Some(_)) => Err(Error::Unexpected(
                 "must not contain both `pattern` and `regexp`.".into(),
@@ -712,9 +822,9 @@ impl Rule {
                     Some("yes") => true,
                     None => false,
                     x => panic!("unknown case_sensitive value {:?}", x),
-                };
+                } && !info.force_case_insensitive;
                 let mark = regex.mark.map_or(0, |x| x.parse().unwrap());
-                let regex = SerializeRegex::new(&regex.text, false, case_sensitive)?;
+                let regex = info.regex(&regex.text, false, case_sensitive)?;
                 let id_to_idx: DefaultHashMap<usize, usize> =
                     (0..regex.captures_len() + 1).enumerate().collect();
                 Ok((Engine::Text(regex, id_to_idx), mark, mark + 1))
@@ -816,6 +926,8 @@ impl Rule {
                                 start: char_length,
                                 end: char_length + length,
                                 replacements,
+                                confidence: None,
+                                sentence_index: 0,
                             });
                         }
 
@@ -839,9 +951,14 @@ impl Rule {
             message: Synthesizer {
                 parts: message_parts,
                 use_titlecase_adjust: true,
+                sentence_case: false,
             },
             url: data.url.map(|x| x.to_string()),
             short: data.short.map(|x| x.to_string()),
+            first_match_only,
+            language: data.language,
+            confidence,
+            priority,
             // attributes below need information from rule group / category, so are set later
             id: String::new(),
             name: String::new(),
@@ -902,11 +1019,7 @@ impl owned::WordData {
 fn parse_pos_filter(postag: &str, postag_regexp: Option<&str>, info: &mut BuildInfo) -> POSFilter {
     match postag_regexp.as_deref() {
         Some("yes") => POSFilter::new(PosMatcher::new(
-            Matcher::new_regex(
-                SerializeRegex::new(&postag, true, true).unwrap(),
-                false,
-                true,
-            ),
+            Matcher::new_regex(info.regex(&postag, true, true).unwrap(), false, true),
             info,
         )),
         Some(_) | None => POSFilter::new(PosMatcher::new(
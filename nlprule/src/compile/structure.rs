@@ -199,6 +199,7 @@ pub enum SuggestionPart {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Suggestion {
+    pub case_conversion: Option<String>,
     #[serde(rename = "$value")]
     pub parts: Vec<SuggestionPart>,
 }
@@ -460,6 +461,11 @@ pub struct Rule {
     pub url: Option<XMLText>,
     pub default: Option<String>,
     pub filter: Option<Filter>,
+    pub first_match_only: Option<String>,
+    #[serde(rename = "lang")]
+    pub language: Option<String>,
+    pub confidence: Option<String>,
+    pub priority: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
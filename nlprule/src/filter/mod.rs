@@ -50,10 +50,10 @@ impl Filterable for NoDisambiguationEnglishPartialPosTagFilter {
             let tokens = &group.tokens(graph.tokens());
 
             tokens.iter().all(|token| {
-                if let Some(captures) = self.regexp.captures(&token.word.text.as_ref()) {
-                    // get group 2 because `must_fully_match` adds one group
+                // get group 2 because `must_fully_match` adds one group
+                if let Some(text) = self.regexp.group_at(&token.word.text.as_ref(), 2) {
                     let tags = tokenizer.tagger().get_tags(
-                        &captures.at(2).unwrap(),
+                        text,
                         tokenizer.options().always_add_lower_tags,
                         tokenizer.options().use_compound_split_heuristic,
                     );
@@ -4,6 +4,7 @@
 //! NLPRule has the following core abstractions:
 //! - A [Tokenizer][tokenizer::Tokenizer] to split a text into tokens and analyze it by chunking, lemmatizing and part-of-speech tagging. Can also be used independently of the grammatical rules.
 //! - A [Rules][rules::Rules] structure containing a set of grammatical error correction rules.
+//! - A [Checker][checker::Checker] bundling rules with their tokenizer for use without managing both e. g. from WebAssembly.
 //!
 //! # Example: correct a text
 //!
@@ -38,7 +39,9 @@
 //!         end: 16,
 //!         replacements: vec!["was not".into(), "has not been".into()],
 //!         source: "WAS_BEEN.1".into(),
-//!         message: "Did you mean was not or has not been?".into()
+//!         message: "Did you mean was not or has not been?".into(),
+//!         confidence: None,
+//!         sentence_index: 0,
 //!     }]
 //! );
 //!
@@ -55,6 +58,7 @@
 //! Almost all structures with a lifetime are bound to this lifetime.
 use thiserror::Error;
 
+pub mod checker;
 #[cfg(feature = "compile")]
 pub mod compile;
 mod filter;
@@ -64,8 +68,12 @@ pub mod tokenizer;
 pub mod types;
 pub(crate) mod utils;
 
+pub use checker::Checker;
 pub use rules::Rules;
 pub use tokenizer::Tokenizer;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub use utils::testing;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -73,4 +81,6 @@ pub enum Error {
     Unexpected(String),
     #[error("feature not implemented: {0}")]
     Unimplemented(String),
+    #[error("invalid regex: {0}")]
+    Regex(String),
 }
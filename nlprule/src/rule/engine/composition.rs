@@ -1,15 +1,37 @@
-use crate::{types::*, utils::regex::SerializeRegex};
+//! Atoms and matchers which make up the pattern of a rule. They can also be used to match tokens directly,
+//! see [Atomable::is_match].
+
+pub use crate::utils::{
+    lang::{Date, Language},
+    regex::SerializeRegex,
+};
+
+use crate::types::*;
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
 use unicase::UniCase;
 
+/// Matches a string against a literal, the text of another group or a regex.
+///
+/// Empty input is never compared against the pattern. Instead, the result for empty input is:
+///
+/// | `negate` | `empty_always_false` | result |
+/// |----------|----------------------|--------|
+/// | `false`  | `false`              | `false`|
+/// | `true`   | `false`              | `true` |
+/// | `false`  | `true`               | `false`|
+/// | `true`   | `true`               | `false`|
+///
+/// If `empty_match` is set, it takes precedence over this table.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Matcher {
     pub matcher: either::Either<either::Either<String, usize>, SerializeRegex>,
     pub negate: bool,
     pub case_sensitive: bool,
     pub empty_always_false: bool,
+    pub empty_match: Option<bool>,
 }
 
 impl Matcher {
@@ -26,6 +48,10 @@ impl Matcher {
 
     pub fn is_match(&self, input: &str, graph: &MatchGraph, case_sensitive: Option<bool>) -> bool {
         if input.is_empty() {
+            if let Some(empty_match) = self.empty_match {
+                return empty_match;
+            }
+
             return if self.empty_always_false {
                 false
             } else {
@@ -64,9 +90,128 @@ impl Matcher {
     }
 }
 
+impl Matcher {
+    /// Gets the literal string this matcher compares against if it is a plain, non-negated string matcher
+    /// which does not match empty input.
+    pub(crate) fn literal(&self) -> Option<&str> {
+        match &self.matcher {
+            either::Left(either::Left(string))
+                if !self.negate && self.empty_match != Some(true) =>
+            {
+                Some(string.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Matches if the input is in a set of strings or, if negated, if it is not.
+/// Replaces a [NotAtom] around an [OrAtom] of string matchers with a single lookup.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetMatcher {
+    #[serde(serialize_with = "crate::utils::sorted::set")]
+    pub set: DefaultHashSet<String>,
+    pub negate: bool,
+}
+
+impl SetMatcher {
+    pub fn is_slice_match<S: AsRef<str>>(&self, input: &[S]) -> bool {
+        let matches = input.iter().any(|x| self.set.contains(x.as_ref()));
+
+        if self.negate {
+            !matches
+        } else {
+            matches
+        }
+    }
+}
+
+/// Matches if the input is contained in a dictionary e. g. a large word list loaded at startup.
+/// The dictionary is shared so it can be used by many atoms without copying it.
+/// If the matcher is case insensitive, the words must be stored in lowercase.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DictionaryMatcher {
+    #[serde(serialize_with = "crate::utils::sorted::set")]
+    pub words: Arc<DefaultHashSet<String>>,
+    pub case_sensitive: bool,
+}
+
+impl DictionaryMatcher {
+    pub fn is_match(&self, input: &str) -> bool {
+        if self.case_sensitive {
+            self.words.contains(input)
+        } else {
+            self.words.contains(&input.to_lowercase())
+        }
+    }
+}
+
+/// A comparison of a number against a bound.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum NumericComparison {
+    LessThan(f64),
+    LessOrEqual(f64),
+    Equal(f64),
+    GreaterThan(f64),
+    GreaterOrEqual(f64),
+    /// Inclusive range from the first to the second number.
+    Range(f64, f64),
+}
+
+impl NumericComparison {
+    pub fn is_match(&self, number: f64) -> bool {
+        match *self {
+            NumericComparison::LessThan(x) => number < x,
+            NumericComparison::LessOrEqual(x) => number <= x,
+            NumericComparison::Equal(x) => (number - x).abs() < f64::EPSILON,
+            NumericComparison::GreaterThan(x) => number > x,
+            NumericComparison::GreaterOrEqual(x) => number >= x,
+            NumericComparison::Range(min, max) => number >= min && number <= max,
+        }
+    }
+}
+
+/// Matches if the input parsed as a number satisfies a comparison e. g. to check that an hour is at most 24.
+/// Never matches if the input is not a number.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NumericMatcher {
+    pub comparison: NumericComparison,
+}
+
+impl NumericMatcher {
+    pub fn is_match(&self, input: &str) -> bool {
+        match input.parse() {
+            Ok(number) => self.comparison.is_match(number),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Matches if the input is a number written in words of the language (e. g. "twenty-three", see [parse_number_words][Language::parse_number_words])
+/// which satisfies the comparison, or any number if there is no comparison. Never matches if the data of the language is not compiled in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NumberWordMatcher {
+    pub language: Language,
+    pub comparison: Option<NumericComparison>,
+}
+
+impl NumberWordMatcher {
+    pub fn is_match(&self, input: &str) -> bool {
+        match self.language.parse_number_words(input) {
+            Some(number) => self.comparison.is_none_or(|x| x.is_match(number as f64)),
+            None => false,
+        }
+    }
+}
+
+/// Matches the text of a token. For regexes, the set of ids of the words in the vocabulary of the tagger which match is
+/// precomputed when building the rules, so known words are matched by a lookup instead of running the regex.
+/// This is the only per-word cache: it accelerates single token matchers in any rule, regardless of how many tokens the rule has,
+/// but is not used for regexes matching more than 100 words, for matchers depending on the match graph (e. g. `\1`) or for unknown words.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TextMatcher {
     pub matcher: Matcher,
+    #[serde(serialize_with = "crate::utils::sorted::option_set")]
     pub set: Option<DefaultHashSet<u32>>,
 }
 
@@ -137,12 +282,100 @@ impl WordDataMatcher {
     }
 }
 
+/// Matches morphological features encoded in a part-of-speech tag e. g. `NOUN:gender=fem:number=plur`
+/// or `Gender=Fem|Number=Plur`. The tag is split at `:` and `|`, segments of the form `key=value` are features.
+/// Matches if the tag has all of the required features, regardless of the rest of the tag.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeatureMatcher {
+    pub features: Vec<(String, String)>,
+}
+
+impl FeatureMatcher {
+    /// Parses the key-value features of a part-of-speech tag. Segments which are not key-value pairs are ignored.
+    pub fn parse(tag: &str) -> impl Iterator<Item = (&str, &str)> {
+        tag.split(&[':', '|'][..]).filter_map(|segment| {
+            let mut parts = segment.splitn(2, '=');
+            Some((parts.next()?, parts.next()?))
+        })
+    }
+
+    pub fn is_match(&self, tag: &str) -> bool {
+        self.features.iter().all(|(key, value)| {
+            FeatureMatcher::parse(tag).any(|(k, v)| k == key.as_str() && v == value.as_str())
+        })
+    }
+}
+
+/// Matches the raw bytes of a text, e. g. to detect stray control characters in OCR output.
+/// Texts are always valid UTF-8, so non-ASCII characters are matched by their UTF-8 encoding.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum ByteMatcher {
+    /// Matches if the bytes contain the sequence. An empty sequence always matches.
+    Sequence(Vec<u8>),
+    /// Matches if any byte is in the inclusive range, e. g. `(0x00, 0x1f)` for the C0 control characters.
+    Range(u8, u8),
+}
+
+impl ByteMatcher {
+    pub fn is_match(&self, bytes: &[u8]) -> bool {
+        match self {
+            ByteMatcher::Sequence(sequence) => {
+                sequence.is_empty() || bytes.windows(sequence.len()).any(|x| x == &sequence[..])
+            }
+            ByteMatcher::Range(min, max) => bytes.iter().any(|x| (*min..=*max).contains(x)),
+        }
+    }
+}
+
+/// The capitalization of a text. Only cased letters (i. e. lowercase or uppercase according to Unicode) are considered,
+/// so e. g. digits, punctuation and letters of scripts without case are ignored. A text without cased letters has no casing.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    /// All cased letters are lowercase e. g. "straße".
+    Lower,
+    /// All cased letters are uppercase e. g. "NASA" or "A".
+    Upper,
+    /// The first cased letter is uppercase and all others are lowercase e. g. "Émile" or "A".
+    Title,
+    /// Both lowercase and uppercase letters, but not title case e. g. "iPhone" or "McDonald".
+    Mixed,
+}
+
+impl Casing {
+    pub fn is_match(&self, text: &str) -> bool {
+        let mut cased = text
+            .chars()
+            .filter(|c| c.is_lowercase() || c.is_uppercase());
+
+        let first_upper = match cased.next() {
+            Some(c) => c.is_uppercase(),
+            None => return false,
+        };
+        let (mut rest_lower, mut rest_upper) = (false, false);
+        for c in cased {
+            rest_lower |= c.is_lowercase();
+            rest_upper |= c.is_uppercase();
+        }
+
+        match self {
+            Casing::Lower => !first_upper && !rest_upper,
+            Casing::Upper => first_upper && !rest_lower,
+            Casing::Title => first_upper && !rest_upper,
+            Casing::Mixed => (!first_upper || rest_lower) && rest_upper,
+        }
+    }
+}
+
+/// How often a [Part] must match consecutively. A part with an always-matching atom and a quantifier of `(0, k)`
+/// is a gap of at most `k` tokens: the part stops consuming tokens once `max` is reached, so a bounded gap
+/// never scans further than `k` tokens for the next part.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Quantifier {
     pub min: usize,
     pub max: usize,
 }
 
+/// Checks whether the token at `position` in `input` matches, given the groups matched so far in `graph`.
 #[enum_dispatch]
 pub trait Atomable: Send + Sync {
     fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool;
@@ -161,11 +394,158 @@ pub enum Atom {
     OrAtom,
     NotAtom,
     OffsetAtom,
+    // new variants must be added at the end to keep the binary format of existing variants
+    ChunkSetAtom(concrete::ChunkSetAtom),
+    ChunkSpanAtom(concrete::ChunkSpanAtom),
+    WhitespaceAtom(concrete::WhitespaceAtom),
+    NumericAtom(concrete::NumericAtom),
+    NumberWordAtom(concrete::NumberWordAtom),
+    DateAtom(concrete::DateAtom),
+    DictionaryAtom(concrete::DictionaryAtom),
+    FeatureAtom(concrete::FeatureAtom),
+    LengthAtom(concrete::LengthAtom),
+    PosSetAtom(concrete::PosSetAtom),
+    SentenceEndAtom(concrete::SentenceEndAtom),
+    FirstWordAtom(concrete::FirstWordAtom),
+    RepeatAtom(concrete::RepeatAtom),
+    CaseAtom(concrete::CaseAtom),
+    ByteAtom(concrete::ByteAtom),
+    UnknownWordAtom(concrete::UnknownWordAtom),
+    AbbreviationAtom(concrete::AbbreviationAtom),
+    LookaheadAtom,
+    LookbehindAtom,
+    PositionAtom,
+    GroupEqualityAtom,
+    GroupFeatureAtom,
+    WithinGroupAtom,
+    #[serde(skip)]
+    PredicateAtom,
+}
+
+impl Matcher {
+    fn describe(&self) -> String {
+        let pattern = match &self.matcher {
+            either::Left(either::Left(string)) => format!("\"{}\"", string),
+            either::Left(either::Right(id)) => format!("\\{}", id),
+            either::Right(_) => "regex".to_string(),
+        };
+
+        if self.negate {
+            format!("!{}", pattern)
+        } else {
+            pattern
+        }
+    }
+}
+
+impl Atom {
+    /// Gets a short human-readable description of what this atom matches e. g. `"the" | "a"`.
+    /// Regexes and part-of-speech masks are not reproduced, only their kind is shown.
+    pub fn describe(&self) -> String {
+        let join = |atoms: &[Atom], sep: &str| {
+            atoms
+                .iter()
+                .map(|x| x.describe())
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+
+        match self {
+            Atom::ChunkAtom(atom) => format!("chunk({})", atom.matcher.describe()),
+            Atom::ChunkSetAtom(atom) => {
+                let mut chunks: Vec<_> = atom.matcher.set.iter().map(|x| x.as_str()).collect();
+                chunks.sort_unstable();
+                let negate = if atom.matcher.negate { "!" } else { "" };
+                format!("{}chunk in [{}]", negate, chunks.join(", "))
+            }
+            Atom::ChunkSpanAtom(atom) => format!("chunk_span({})", atom.chunk),
+            Atom::SpaceBeforeAtom(atom) => format!("space_before({})", atom.value),
+            Atom::WhitespaceAtom(atom) => format!("whitespace({})", atom.matcher.describe()),
+            Atom::TextAtom(atom) => atom.matcher.matcher.describe(),
+            Atom::NumericAtom(atom) => format!("number({:?})", atom.matcher.comparison),
+            Atom::NumberWordAtom(atom) => format!(
+                "number_words({:?}, {:?})",
+                atom.matcher.language, atom.matcher.comparison
+            ),
+            Atom::DateAtom(atom) => format!("date({:?}, valid: {:?})", atom.language, atom.valid),
+            Atom::DictionaryAtom(_) => "dictionary".to_string(),
+            Atom::FeatureAtom(atom) => format!(
+                "features({})",
+                atom.matcher
+                    .features
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Atom::LengthAtom(atom) => format!("length({}..={})", atom.min, atom.max),
+            Atom::WordDataAtom(_) => "word_data".to_string(),
+            Atom::PosSetAtom(atom) => {
+                let mut tags: Vec<_> = atom.tags.iter().map(|x| x.as_str()).collect();
+                tags.sort_unstable();
+                format!("pos in [{}]", tags.join(", "))
+            }
+            Atom::SentenceEndAtom(_) => "sentence_end".to_string(),
+            Atom::FirstWordAtom(_) => "first_word".to_string(),
+            Atom::RepeatAtom(_) => "repeat".to_string(),
+            Atom::CaseAtom(atom) => format!("case({:?})", atom.casing),
+            Atom::ByteAtom(atom) => format!("bytes({:?})", atom.matcher),
+            Atom::UnknownWordAtom(_) => "unknown".to_string(),
+            Atom::AbbreviationAtom(_) => "abbreviation".to_string(),
+            Atom::TrueAtom(_) => "*".to_string(),
+            Atom::FalseAtom(_) => "never".to_string(),
+            Atom::AndAtom(atom) => format!("({})", join(&atom.atoms, " & ")),
+            Atom::OrAtom(atom) => format!("({})", join(&atom.atoms, " | ")),
+            Atom::NotAtom(atom) => format!("!{}", atom.atom.describe()),
+            Atom::OffsetAtom(atom) => format!("offset({}, {})", atom.offset, atom.atom.describe()),
+            Atom::LookaheadAtom(atom) => format!(
+                "lookahead({}..={}, {})",
+                atom.min,
+                atom.max,
+                atom.atom.describe()
+            ),
+            Atom::LookbehindAtom(atom) => format!(
+                "lookbehind({}..={}, {})",
+                atom.min,
+                atom.max,
+                atom.atom.describe()
+            ),
+            Atom::PositionAtom(atom) => format!("position({:?})", atom.predicate),
+            Atom::GroupEqualityAtom(atom) => format!("\\{} == \\{}", atom.a, atom.b),
+            Atom::GroupFeatureAtom(atom) => {
+                format!("{} == {}(\\{})", atom.feature, atom.feature, atom.group)
+            }
+            Atom::WithinGroupAtom(atom) => format!("within(\\{})", atom.group),
+            Atom::PredicateAtom(_) => "predicate".to_string(),
+        }
+    }
+
+    /// Literal token texts of which one must occur at the matched position for this atom to match.
+    /// Returns `None` if no such set can be determined e. g. for regex or part-of-speech matchers.
+    pub(crate) fn required_literals(&self) -> Option<Vec<&str>> {
+        match self {
+            Atom::TextAtom(atom) => atom.matcher.matcher.literal().map(|x| vec![x]),
+            Atom::AndAtom(atom) => atom.atoms.iter().find_map(|x| x.required_literals()),
+            Atom::OrAtom(atom) => atom
+                .atoms
+                .iter()
+                .map(|x| x.required_literals())
+                .collect::<Option<Vec<_>>>()
+                .map(|x| x.into_iter().flatten().collect()),
+            _ => None,
+        }
+    }
 }
 
 pub mod concrete {
-    use super::{Atomable, MatchGraph, Matcher, TextMatcher, Token, WordDataMatcher};
+    use super::{
+        Atomable, ByteMatcher, Casing, DefaultHashSet, DictionaryMatcher, FeatureMatcher, Language,
+        MatchGraph, Matcher, NumberWordMatcher, NumericMatcher, SetMatcher, TextMatcher, Token,
+        WordDataMatcher,
+    };
     use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use unicase::UniCase;
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct TextAtom {
@@ -179,6 +559,96 @@ pub mod concrete {
         }
     }
 
+    /// Matches if the token text is in a dictionary, see [DictionaryMatcher]. Wrap in a [NotAtom][super::NotAtom]
+    /// to match unknown words.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DictionaryAtom {
+        pub(crate) matcher: DictionaryMatcher,
+    }
+
+    impl Atomable for DictionaryAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            self.matcher.is_match(input[position].word.text.as_ref())
+        }
+    }
+
+    /// Matches if any part-of-speech tag of the token has the features of the [FeatureMatcher].
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct FeatureAtom {
+        pub(crate) matcher: FeatureMatcher,
+    }
+
+    impl Atomable for FeatureAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            input[position]
+                .word
+                .tags
+                .iter()
+                .any(|x| self.matcher.is_match(x.pos.as_ref()))
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct NumericAtom {
+        pub(crate) matcher: NumericMatcher,
+    }
+
+    impl Atomable for NumericAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            self.matcher.is_match(input[position].word.text.as_ref())
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct NumberWordAtom {
+        pub(crate) matcher: NumberWordMatcher,
+    }
+
+    impl Atomable for NumberWordAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            self.matcher.is_match(input[position].word.text.as_ref())
+        }
+    }
+
+    /// Matches the first token of a date in the language (see [parse_date][Language::parse_date] for the formats)
+    /// if the date exists, or if it does not exist e. g. "31st of February" if `valid` is `false`. Matches any date if `valid` is `None`.
+    /// The following tokens of the date are only checked, not consumed, so they have to be matched by later parts of the pattern.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DateAtom {
+        pub(crate) language: Language,
+        pub(crate) valid: Option<bool>,
+    }
+
+    impl Atomable for DateAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            // dates span at most four tokens
+            let words: Vec<_> = input[position..]
+                .iter()
+                .take(4)
+                .map(|x| x.word.text.as_ref())
+                .collect();
+
+            match self.language.parse_date(&words) {
+                Some((date, _)) => self.valid.is_none_or(|x| x == date.is_valid()),
+                None => false,
+            }
+        }
+    }
+
+    /// Matches if the number of chars (i. e. code points, not bytes) of the token text is between `min` and `max` (inclusive).
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct LengthAtom {
+        pub(crate) min: usize,
+        pub(crate) max: usize,
+    }
+
+    impl Atomable for LengthAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            let length = input[position].word.text.as_ref().chars().count();
+            length >= self.min && length <= self.max
+        }
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct ChunkAtom {
         pub(crate) matcher: Matcher,
@@ -191,6 +661,39 @@ pub mod concrete {
         }
     }
 
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct ChunkSetAtom {
+        pub(crate) matcher: SetMatcher,
+    }
+
+    impl Atomable for ChunkSetAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            self.matcher.is_slice_match(&input[position].chunks)
+        }
+    }
+
+    /// Matches the tokens of a phrase with the chunk tags `B-{chunk}` and `I-{chunk}` e. g. a noun phrase with `B-NP` and `I-NP`.
+    /// Intended for a part with a quantifier of `1..` so the part matches a full phrase as one group: a token starting a phrase
+    /// only matches as the first token of the group with index `group`, every other token of the phrase only continues the group.
+    /// So the group never extends into the next phrase, even if the phrases are adjacent.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct ChunkSpanAtom {
+        pub(crate) chunk: String,
+        pub(crate) group: usize,
+    }
+
+    impl Atomable for ChunkSpanAtom {
+        fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+            let continues = graph.groups.get(self.group).is_some_and(|x| x.matched);
+            let prefix = if continues { "I-" } else { "B-" };
+
+            input[position]
+                .chunks
+                .iter()
+                .any(|x| x.strip_prefix(prefix) == Some(self.chunk.as_str()))
+        }
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct SpaceBeforeAtom {
         pub(crate) value: bool,
@@ -202,6 +705,168 @@ pub mod concrete {
         }
     }
 
+    /// Matches the whitespace directly preceding the token (see [whitespace_before][Token::whitespace_before])
+    /// e. g. to distinguish a non-breaking space from a regular space. The input is empty if there is no whitespace.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WhitespaceAtom {
+        pub(crate) matcher: Matcher,
+    }
+
+    impl Atomable for WhitespaceAtom {
+        fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+            self.matcher
+                .is_match(input[position].whitespace_before(), graph, None)
+        }
+    }
+
+    /// Matches if the token has any of the part-of-speech tags in the set.
+    /// A faster alternative to an [OrAtom][super::OrAtom] of [WordDataAtom]s which only check the tag.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PosSetAtom {
+        #[serde(serialize_with = "crate::utils::sorted::set")]
+        pub(crate) tags: DefaultHashSet<String>,
+    }
+
+    impl Atomable for PosSetAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            input[position]
+                .word
+                .tags
+                .iter()
+                .any(|x| self.tags.contains(x.pos.as_ref()))
+        }
+    }
+
+    /// Matches if the token is a known abbreviation e. g. "Fig" in "Fig. 3". The abbreviations are the ones of the
+    /// tokenizer (see [abbreviations][crate::tokenizer::Tokenizer::abbreviations]) and include the trailing period,
+    /// so a token matches if it is directly followed by a period and the two together are in the set.
+    /// Abbreviations which are split into more than two tokens (e. g. "e.g.") do not match.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct AbbreviationAtom {
+        #[serde(serialize_with = "crate::utils::sorted::set")]
+        pub(crate) abbreviations: Arc<DefaultHashSet<String>>,
+    }
+
+    impl Atomable for AbbreviationAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            let text = input[position].word.text.as_ref();
+
+            if text.is_empty() || text == "." {
+                return false;
+            }
+
+            self.abbreviations.contains(text)
+                || input.get(position + 1).is_some_and(|next| {
+                    next.word.text.as_ref() == "."
+                        && !next.has_space_before
+                        && self.abbreviations.contains(&format!("{}.", text))
+                })
+        }
+    }
+
+    /// Matches if the token has the same text as the token directly before it e. g. the second "the" in "the the cat".
+    /// A self-contained alternative to a [GroupEqualityAtom][super::GroupEqualityAtom] to detect accidental repetition.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct RepeatAtom {
+        pub(crate) case_sensitive: bool,
+    }
+
+    impl Atomable for RepeatAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            if position == 0 {
+                return false;
+            }
+
+            let text = input[position].word.text.as_ref();
+            let prev = input[position - 1].word.text.as_ref();
+
+            if text.is_empty() {
+                false
+            } else if self.case_sensitive {
+                text == prev
+            } else {
+                UniCase::new(text) == UniCase::new(prev)
+            }
+        }
+    }
+
+    /// Matches if the token text has the given [Casing].
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct CaseAtom {
+        pub(crate) casing: Casing,
+    }
+
+    impl Atomable for CaseAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            self.casing.is_match(input[position].word.text.as_ref())
+        }
+    }
+
+    /// Matches the bytes of the token in the original text with a [ByteMatcher]. Unlike the other text atoms,
+    /// this does not use the word text of the token, so it sees characters as they were before any normalization.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct ByteAtom {
+        pub(crate) matcher: ByteMatcher,
+    }
+
+    impl Atomable for ByteAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            let token = input[position];
+            self.matcher
+                .is_match(&token.text.as_bytes()[token.byte_span.0..token.byte_span.1])
+        }
+    }
+
+    /// Matches if the tagger did not recognize the token, see [is_known][Token::is_known]. Useful for a catch-all
+    /// rule about possible misspellings without maintaining a separate dictionary as for a [DictionaryAtom].
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    pub struct UnknownWordAtom {}
+
+    impl Atomable for UnknownWordAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            let token = input[position];
+            !token.word.text.as_ref().is_empty() && !token.is_known()
+        }
+    }
+
+    /// Matches the punctuation token ending a sentence i. e. a token which the tokenizer tagged as `SENT_END`
+    /// and which does not contain any alphanumeric characters. Independent of the language-specific set of terminal punctuation.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct SentenceEndAtom {}
+
+    impl Atomable for SentenceEndAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            let token = input[position];
+
+            token.word.tags.iter().any(|x| x.pos.as_ref() == "SENT_END")
+                && !token.word.text.as_ref().is_empty()
+                && !token.word.text.as_ref().chars().any(char::is_alphanumeric)
+        }
+    }
+
+    /// Matches the first token of a sentence which contains a letter, skipping leading tokens without letters
+    /// such as quotes, brackets or dashes e. g. for capitalization rules. A sentence starts after the sentence start token
+    /// or after a token tagged as `SENT_END`.
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    pub struct FirstWordAtom {}
+
+    impl Atomable for FirstWordAtom {
+        fn is_match(&self, input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+            let has_letter =
+                |token: &Token| token.word.text.as_ref().chars().any(char::is_alphabetic);
+
+            has_letter(input[position])
+                && input[..position]
+                    .iter()
+                    .rev()
+                    .take_while(|x| {
+                        x.char_span.0 != x.char_span.1
+                            && !x.word.tags.iter().any(|x| x.pos.as_ref() == "SENT_END")
+                    })
+                    .all(|x| !has_letter(x))
+        }
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct WordDataAtom {
         pub(crate) matcher: WordDataMatcher,
@@ -291,41 +956,221 @@ impl Atomable for OffsetAtom {
     }
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct Group {
-    pub char_span: (usize, usize),
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PositionPredicate {
+    Equals(usize),
+    Every(usize),
+    GreaterThan(usize),
 }
 
-impl Group {
-    pub fn new(char_span: (usize, usize)) -> Self {
-        Group { char_span }
+/// Matches based on the absolute index of the token in the sentence (the sentence start token has index zero).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PositionAtom {
+    pub(crate) predicate: PositionPredicate,
+}
+
+impl Atomable for PositionAtom {
+    fn is_match(&self, _input: &[&Token], _graph: &MatchGraph, position: usize) -> bool {
+        match self.predicate {
+            PositionPredicate::Equals(n) => position == n,
+            PositionPredicate::Every(k) => position.checked_rem(k) == Some(0),
+            PositionPredicate::GreaterThan(n) => position > n,
+        }
     }
+}
 
-    pub fn tokens<'t>(&self, tokens: &[&'t Token<'t>]) -> Vec<&'t Token<'t>> {
-        tokens
-            .iter()
-            .filter_map(|x| {
-                if x.char_span.1 > x.char_span.0 // special tokens with zero range (e. g. SENT_START) can not be part of groups
-                    && x.char_span.0 >= self.char_span.0
-                    && x.char_span.1 <= self.char_span.1
-                {
-                    Some(*x)
-                } else {
-                    None
+/// Zero-width assertion that the atom matches at any of the positions `position + min..=position + max`.
+/// Does not consume the tokens it checks so they are not part of the group of the current position.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LookaheadAtom {
+    pub(crate) atom: Box<Atom>,
+    pub(crate) min: usize,
+    pub(crate) max: usize,
+}
+
+impl Atomable for LookaheadAtom {
+    fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+        (position + self.min..=position + self.max)
+            .take_while(|i| *i < input.len())
+            .any(|i| self.atom.is_match(input, graph, i))
+    }
+}
+
+/// Zero-width assertion that the atom matches at any of the positions `position - max..=position - min`.
+/// Does not consume the tokens it checks so they are not part of the group of the current position.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LookbehindAtom {
+    pub(crate) atom: Box<Atom>,
+    pub(crate) min: usize,
+    pub(crate) max: usize,
+}
+
+impl Atomable for LookbehindAtom {
+    fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+        (self.min..=self.max)
+            .take_while(|offset| *offset <= position)
+            .any(|offset| self.atom.is_match(input, graph, position - offset))
+    }
+}
+
+/// Matches if the groups with the ids `a` and `b` have the same text e. g. to detect repeated words.
+/// Does not depend on the token at the current position. Never matches if one of the groups does not exist or is empty.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupEqualityAtom {
+    pub(crate) a: usize,
+    pub(crate) b: usize,
+}
+
+impl Atomable for GroupEqualityAtom {
+    fn is_match(&self, _input: &[&Token], graph: &MatchGraph, _position: usize) -> bool {
+        let text = graph.tokens.first().map_or("", |x| x.text);
+
+        match (graph.by_id(self.a), graph.by_id(self.b)) {
+            (Some(a), Some(b)) => {
+                let a = a.text(text);
+                !a.is_empty() && a == b.text(text)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Matches if the token shares a value of the morphological feature `feature` (see [FeatureMatcher]) with the head token
+/// of the group with the id `group` e. g. for agreement in number between a verb and its subject. The head is the last token of the group.
+/// The values of all part-of-speech tags are considered, so an ambiguous token agrees if any of its readings agrees.
+/// Never matches if the group does not exist or is empty, or if one of the tokens does not have the feature.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupFeatureAtom {
+    pub(crate) group: usize,
+    pub(crate) feature: String,
+}
+
+impl GroupFeatureAtom {
+    fn values<'a>(&'a self, token: &'a Token) -> impl Iterator<Item = &'a str> {
+        token.word.tags.iter().flat_map(move |x| {
+            FeatureMatcher::parse(x.pos.as_ref())
+                .filter(move |(key, _)| *key == self.feature)
+                .map(|(_, value)| value)
+        })
+    }
+}
+
+impl Atomable for GroupFeatureAtom {
+    fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+        let head = match graph
+            .by_id(self.group)
+            .and_then(|x| x.tokens(graph.tokens()).last().copied())
+        {
+            Some(head) => head,
+            None => return false,
+        };
+
+        let head_values: Vec<_> = self.values(head).collect();
+        self.values(input[position])
+            .any(|value| head_values.contains(&value))
+    }
+}
+
+/// Matches if the token at the current position is one of the tokens of the group with the id `group`,
+/// e. g. together with an [OffsetAtom] or [LookbehindAtom] to refer to the position of an earlier capture.
+/// Never matches if the group does not exist or is empty.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WithinGroupAtom {
+    pub(crate) group: usize,
+}
+
+impl Atomable for WithinGroupAtom {
+    fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+        let token = input[position];
+
+        graph.by_id(self.group).is_some_and(|group| {
+            token.char_span.1 > token.char_span.0 // consistent with Group::tokens
+                && token.char_span.0 >= group.char_span.0
+                && token.char_span.1 <= group.char_span.1
+        })
+    }
+}
+
+/// Matches if a user-supplied function returns `true` for the token at the current position, e. g. to prototype
+/// a condition without adding a new atom type. Functions can not be serialized, so rules containing this atom
+/// fail to serialize and have to be constructed at runtime.
+pub struct PredicateAtom {
+    pub(crate) predicate: Predicate,
+}
+
+/// A function deciding whether a token matches given the groups matched so far, see [PredicateAtom].
+pub type Predicate = Box<dyn Fn(&Token, &MatchGraph) -> bool + Send + Sync>;
+
+impl fmt::Debug for PredicateAtom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PredicateAtom").finish_non_exhaustive()
+    }
+}
+
+impl Atomable for PredicateAtom {
+    fn is_match(&self, input: &[&Token], graph: &MatchGraph, position: usize) -> bool {
+        (self.predicate)(input[position], graph)
+    }
+}
+
+/// The part of the text matched by one part of a pattern.
+///
+/// If an optional part did not match any tokens, `matched` is `false` and the span is set to the zero-width
+/// boundary between the neighboring groups, so it can be distinguished from a group which matched at that point.
+#[derive(Debug, Default, Clone)]
+pub struct Group {
+    pub char_span: (usize, usize),
+    pub matched: bool,
+}
+
+impl Group {
+    /// Creates a group which matched the text between the given chars.
+    pub fn new(char_span: (usize, usize)) -> Self {
+        Group {
+            char_span,
+            matched: true,
+        }
+    }
+
+    pub fn tokens<'t>(&self, tokens: &[&'t Token<'t>]) -> Vec<&'t Token<'t>> {
+        tokens
+            .iter()
+            .filter_map(|x| {
+                if x.char_span.1 > x.char_span.0 // special tokens with zero range (e. g. SENT_START) can not be part of groups
+                    && x.char_span.0 >= self.char_span.0
+                    && x.char_span.1 <= self.char_span.1
+                {
+                    Some(*x)
+                } else {
+                    None
                 }
             })
             .collect()
     }
 
     pub fn text<'a>(&self, text: &'a str) -> &'a str {
+        self.raw_text(text)
+    }
+
+    /// Slices the original source text between the start and end char of this group.
+    /// Unlike the text of the tokens in the group, this includes whitespace and other characters
+    /// between the tokens e. g. to check spacing in typography rules. `source` must be the text the tokens are from.
+    pub fn raw_text<'a>(&self, source: &'a str) -> &'a str {
         if self.char_span.0 >= self.char_span.1 {
             return "";
         }
 
-        let mut char_indices: Vec<_> = text.char_indices().map(|(i, _)| i).collect();
-        char_indices.push(text.len());
+        let mut char_indices = source
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(source.len()));
 
-        &text[char_indices[self.char_span.0]..char_indices[self.char_span.1]]
+        let start = char_indices.nth(self.char_span.0).unwrap_or(source.len());
+        let end = char_indices
+            .nth(self.char_span.1 - self.char_span.0 - 1)
+            .unwrap_or(source.len());
+
+        &source[start..end]
     }
 }
 
@@ -383,33 +1228,64 @@ impl<'t> MatchGraph<'t> {
         &self.tokens[..]
     }
 
-    pub fn fill_empty(&mut self) {
-        let mut start = self
-            .groups
+    /// Gets the number of tokens each part of the pattern consumed, in the order of the parts, i. e. the number
+    /// of tokens of every group except the whole match.
+    pub fn consumption(&self) -> Vec<usize> {
+        self.groups
             .iter()
-            .find_map(|x| {
-                let tokens = x.tokens(&self.tokens);
-                if tokens.is_empty() {
-                    None
-                } else {
-                    Some(tokens[0].char_span.0)
-                }
-            })
-            .expect("graph must contain at least one token");
+            .skip(1)
+            .map(|x| x.tokens(self.tokens).len())
+            .collect()
+    }
 
-        let mut end = self
-            .groups
-            .iter()
-            .rev()
-            .find_map(|x| {
-                let tokens = x.tokens(&self.tokens);
-                if tokens.is_empty() {
-                    None
-                } else {
-                    Some(tokens[tokens.len() - 1].char_span.1)
-                }
-            })
-            .expect("graph must contain at least one token");
+    /// Converts this graph to an owned graph which does not borrow the tokens.
+    pub fn to_owned_graph(&self) -> owned::MatchGraph {
+        let text = self.tokens.first().map_or("", |x| x.text);
+        let consumption = self.consumption();
+        let n_tokens = std::iter::once(consumption.iter().sum()).chain(consumption);
+
+        owned::MatchGraph {
+            groups: self
+                .groups
+                .iter()
+                .zip(n_tokens)
+                .map(|(x, n_tokens)| owned::Group {
+                    char_span: x.char_span,
+                    matched: x.matched,
+                    n_tokens,
+                    text: x.text(text).to_string(),
+                })
+                .collect(),
+            id_to_idx: self.id_to_idx.iter().map(|(id, idx)| (*id, *idx)).collect(),
+        }
+    }
+
+    /// Sets the spans of empty groups to the boundary between their neighbors.
+    /// Does nothing if no group contains any tokens.
+    pub fn fill_empty(&mut self) {
+        let mut start = match self.groups.iter().find_map(|x| {
+            let tokens = x.tokens(&self.tokens);
+            if tokens.is_empty() {
+                None
+            } else {
+                Some(tokens[0].char_span.0)
+            }
+        }) {
+            Some(start) => start,
+            None => return,
+        };
+
+        let mut end = match self.groups.iter().rev().find_map(|x| {
+            let tokens = x.tokens(&self.tokens);
+            if tokens.is_empty() {
+                None
+            } else {
+                Some(tokens[tokens.len() - 1].char_span.1)
+            }
+        }) {
+            Some(end) => end,
+            None => return,
+        };
 
         let group_tokens: Vec<_> = self
             .groups
@@ -446,11 +1322,21 @@ pub struct Part {
 #[derive(Serialize, Deserialize)]
 pub struct Composition {
     pub(crate) parts: Vec<Part>,
+    #[serde(serialize_with = "crate::utils::sorted::map")]
     pub(crate) group_ids_to_idx: DefaultHashMap<usize, usize>,
     pub(crate) can_stop_mask: Vec<bool>,
 }
 
 impl Composition {
+    /// Literal token texts of which at least one must occur in the tokens for this composition to match.
+    /// Returns `None` if no required part matches on literal text.
+    pub(crate) fn required_literals(&self) -> Option<Vec<&str>> {
+        self.parts
+            .iter()
+            .filter(|x| x.quantifier.min > 0)
+            .find_map(|x| x.atom.required_literals())
+    }
+
     fn next_can_match<'t>(
         &self,
         tokens: &'t [&'t Token<'t>],
@@ -480,17 +1366,82 @@ impl Composition {
         tokens: &'t [&'t Token<'t>],
         start: usize,
     ) -> Option<MatchGraph<'t>> {
+        // the default graph does not allocate, so a graph is only allocated if the first matcher matched
+        let mut graph = MatchGraph::default();
+
+        if self.apply_into(tokens, start, &mut graph) {
+            Some(graph)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the matches starting at every position in the tokens, in order of their start.
+    /// If `overlapping` is `false`, scanning continues after the last token of a match so matches never overlap.
+    pub fn find_all<'t>(
+        &'t self,
+        tokens: &'t [&'t Token<'t>],
+        overlapping: bool,
+    ) -> Vec<MatchGraph<'t>> {
+        let mut graphs = Vec::new();
+        let mut start = 0;
+
+        while start < tokens.len() {
+            match self.apply(tokens, start) {
+                Some(graph) => {
+                    let end = graph
+                        .groups()
+                        .iter()
+                        .map(|x| x.char_span.1)
+                        .max()
+                        .unwrap_or(0);
+
+                    start = if overlapping {
+                        start + 1
+                    } else {
+                        // continue at the first token after the match, but always make progress
+                        tokens[start + 1..]
+                            .iter()
+                            .position(|x| x.char_span.0 >= end)
+                            .map_or(tokens.len(), |i| start + 1 + i)
+                    };
+
+                    graphs.push(graph);
+                }
+                None => start += 1,
+            }
+        }
+
+        graphs
+    }
+
+    /// Like [apply][Composition::apply], but writes the match into a caller-owned `graph` instead of allocating a new one.
+    /// The graph is cleared before use so it can be reused across calls. Returns whether the composition matched,
+    /// the contents of the graph are unspecified if it did not.
+    ///
+    /// A match must consume at least one token, so this returns `false` if `start` is out of bounds
+    /// (e. g. for empty `tokens`) or if all parts are optional and none of them matched.
+    pub fn apply_into<'t>(
+        &'t self,
+        tokens: &'t [&'t Token<'t>],
+        start: usize,
+        graph: &mut MatchGraph<'t>,
+    ) -> bool {
         // this path is extremely hot so more optimizations are done
 
+        if start >= tokens.len() {
+            return false;
+        }
+
         // the first matcher can never rely on the match graph, so we use an empty default graph for the first match
-        // then allocate a new graph if the first matcher matched
+        // then set up the caller's graph if the first matcher matched
         lazy_static! {
             static ref DEFAULT_GRAPH: MatchGraph<'static> = MatchGraph::default();
         };
 
         let first_must_match = self.parts[0].quantifier.min > 0;
         if first_must_match && !self.parts[0].atom.is_match(tokens, &DEFAULT_GRAPH, start) {
-            return None;
+            return false;
         }
 
         let mut position = start;
@@ -498,11 +1449,10 @@ impl Composition {
         let mut cur_count = 0;
         let mut cur_atom_idx = 0;
 
-        let mut graph = MatchGraph::new(
-            vec![Group::default(); self.parts.len() + 1],
-            &self.group_ids_to_idx,
-            tokens,
-        );
+        graph.groups.clear();
+        graph.groups.resize(self.parts.len() + 1, Group::default());
+        graph.id_to_idx = &self.group_ids_to_idx;
+        graph.tokens = tokens;
 
         let mut is_match = loop {
             if cur_atom_idx >= self.parts.len() {
@@ -525,12 +1475,12 @@ impl Composition {
             }
 
             if cur_count >= part.quantifier.min
-                && self.next_can_match(&tokens, &graph, position, cur_atom_idx)
+                && self.next_can_match(&tokens, graph, position, cur_atom_idx)
             {
                 cur_atom_idx += 1;
                 cur_count = 0;
             } else if (first_must_match && position == start && cur_atom_idx == 0) // we already know this must have matched, otherwise it would have early exited above
-                || part.atom.is_match(tokens, &graph, position)
+                || part.atom.is_match(tokens, graph, position)
             {
                 let mut group = &mut graph.groups[cur_atom_idx + 1];
 
@@ -539,6 +1489,7 @@ impl Composition {
                     group.char_span.0 = tokens[position].char_span.0;
                 }
                 group.char_span.1 = tokens[position].char_span.1;
+                group.matched = true;
 
                 position += 1;
                 cur_count += 1;
@@ -548,12 +1499,987 @@ impl Composition {
         };
 
         is_match = is_match || cur_atom_idx == self.parts.len() || self.can_stop_mask[cur_atom_idx];
+        // an empty match has no position in the text
+        is_match = is_match && position > start;
 
         if is_match {
             graph.fill_empty();
-            Some(graph)
-        } else {
-            None
+            graph.groups[0].matched = true;
+        }
+
+        is_match
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tokenizer::tag::Tagger,
+        utils::testing::{self, composition, text_atom, tokenize},
+    };
+
+    #[test]
+    fn position_atom_matches_absolute_index() {
+        let graph = MatchGraph::default();
+        let matches = |predicate| {
+            let atom = PositionAtom { predicate };
+            (0..6)
+                .filter(|i| atom.is_match(&[], &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(PositionPredicate::Equals(3)), vec![3]);
+        assert_eq!(matches(PositionPredicate::Every(2)), vec![0, 2, 4]);
+        assert!(matches(PositionPredicate::Every(0)).is_empty());
+        assert_eq!(matches(PositionPredicate::GreaterThan(3)), vec![4, 5]);
+    }
+
+    #[test]
+    fn gap_respects_max() {
+        let tagger = Tagger::default();
+
+        let mut pattern = composition(vec![text_atom("a"), TrueAtom {}.into(), text_atom("b")]);
+        pattern.parts[1].quantifier = Quantifier { min: 0, max: 2 };
+        pattern.parts[1].visible = false;
+
+        let matches = |text| {
+            let tokens = tokenize(text, &tagger);
+            let refs: Vec<_> = tokens.iter().collect();
+            pattern.apply(&refs, 0).is_some()
+        };
+
+        assert!(matches("a b"));
+        assert!(matches("a x b"));
+        assert!(matches("a x x b"));
+        assert!(!matches("a x x x b"));
+        assert!(!matches("a x x x x x x b"));
+    }
+
+    #[test]
+    fn marks_absent_optional_groups() {
+        let tagger = Tagger::default();
+
+        let mut pattern = composition(vec![text_atom("a"), text_atom("x"), text_atom("b")]);
+        pattern.parts[1].quantifier = Quantifier { min: 0, max: 1 };
+
+        let matched = |text| {
+            let tokens = tokenize(text, &tagger);
+            let refs: Vec<_> = tokens.iter().collect();
+            let graph = pattern.apply(&refs, 0).unwrap();
+
+            graph.groups().iter().map(|x| x.matched).collect::<Vec<_>>()
+        };
+
+        assert_eq!(matched("a x b"), vec![true, true, true, true]);
+        assert_eq!(matched("a b"), vec![true, true, false, true]);
+    }
+
+    #[test]
+    fn can_get_consumption() {
+        let tagger = Tagger::default();
+        let tokens = tokenize("a x x b", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let mut pattern = composition(vec![text_atom("a"), TrueAtom {}.into(), text_atom("b")]);
+        pattern.parts[1].quantifier = Quantifier { min: 0, max: 3 };
+
+        let graph = pattern.apply(&refs, 0).unwrap();
+        assert_eq!(graph.consumption(), vec![1, 2, 1]);
+        assert_eq!(graph.to_owned_graph().consumption(), vec![1, 2, 1]);
+        assert_eq!(graph.to_owned_graph().by_index(0).n_tokens, 4);
+    }
+
+    #[test]
+    fn can_find_all_matches() {
+        let tagger = Tagger::default();
+        let tokens = tokenize("a a a b a", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let pattern = composition(vec![text_atom("a"), text_atom("a")]);
+        let starts = |overlapping| {
+            pattern
+                .find_all(&refs, overlapping)
+                .iter()
+                .map(|x| x.by_index(0).char_span.0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(starts(true), vec![0, 2]);
+        assert_eq!(starts(false), vec![0]);
+    }
+
+    #[test]
+    fn apply_into_reuses_graph() {
+        let tagger = Tagger::default();
+        let tokens = tokenize("a b c", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let long = composition(vec![text_atom("a"), text_atom("b"), text_atom("c")]);
+        let short = composition(vec![text_atom("c")]);
+
+        let mut graph = MatchGraph::default();
+
+        assert!(long.apply_into(&refs, 0, &mut graph));
+        assert_eq!(graph.groups().len(), 4);
+        assert!(!short.apply_into(&refs, 0, &mut graph));
+        assert!(short.apply_into(&refs, 2, &mut graph));
+        assert_eq!(graph.groups().len(), 2);
+        assert_eq!(
+            graph.by_id(1).unwrap().char_span,
+            short.apply(&refs, 2).unwrap().by_id(1).unwrap().char_span
+        );
+    }
+
+    #[test]
+    fn lookaround_does_not_consume_tokens() {
+        let tagger = Tagger::default();
+
+        let lookahead = AndAtom {
+            atoms: vec![
+                text_atom("b"),
+                LookaheadAtom {
+                    atom: Box::new(text_atom("c")),
+                    min: 1,
+                    max: 1,
+                }
+                .into(),
+            ],
+        };
+        let lookbehind = AndAtom {
+            atoms: vec![
+                text_atom("c"),
+                LookbehindAtom {
+                    atom: Box::new(text_atom("x")),
+                    min: 2,
+                    max: 3,
+                }
+                .into(),
+            ],
+        };
+
+        let pattern = composition(vec![text_atom("a"), lookahead.into()]);
+
+        let tokens = tokenize("x a b c", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+        let graph = pattern.apply(&refs, 1).unwrap();
+        assert_eq!(graph.by_id(1).unwrap().char_span, (2, 3));
+        assert_eq!(graph.by_id(2).unwrap().char_span, (4, 5));
+
+        let tokens = tokenize("x a b d", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+        assert!(pattern.apply(&refs, 1).is_none());
+
+        let pattern = composition(vec![text_atom("b"), lookbehind.into()]);
+
+        let tokens = tokenize("x a b c", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+        let graph = pattern.apply(&refs, 2).unwrap();
+        assert_eq!(graph.by_id(1).unwrap().char_span, (4, 5));
+        assert_eq!(graph.by_id(2).unwrap().char_span, (6, 7));
+
+        let tokens = tokenize("a b c", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+        assert!(pattern.apply(&refs, 1).is_none());
+    }
+
+    #[test]
+    fn numeric_matcher_compares_numbers() {
+        let matcher = |comparison| NumericMatcher { comparison };
+
+        let at_most_24 = matcher(NumericComparison::LessOrEqual(24.));
+        assert!(at_most_24.is_match("24"));
+        assert!(at_most_24.is_match("-3"));
+        assert!(!at_most_24.is_match("25"));
+
+        let between = matcher(NumericComparison::Range(0.5, 1.5));
+        assert!(between.is_match("0.5"));
+        assert!(between.is_match("1.25"));
+        assert!(!between.is_match("1.51"));
+
+        assert!(matcher(NumericComparison::Equal(2.)).is_match("2.0"));
+        assert!(matcher(NumericComparison::LessThan(2.)).is_match("1.9"));
+        assert!(!matcher(NumericComparison::GreaterThan(2.)).is_match("2"));
+
+        for input in &["", "abc", "12h", "1,5"] {
+            assert!(!at_most_24.is_match(input));
+            assert!(!matcher(NumericComparison::GreaterOrEqual(0.)).is_match(input));
+        }
+
+        let tagger = Tagger::default();
+        let tokens = tokenize("at 25 o'clock", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+        let atom = concrete::NumericAtom {
+            matcher: at_most_24,
+        };
+        assert!((0..refs.len()).all(|i| !atom.is_match(&refs, &MatchGraph::default(), i)));
+    }
+
+    #[test]
+    fn length_atom_counts_chars() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+        let tokens = tokenize("ab äöü 日本語です Straße", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |min, max| {
+            let atom = concrete::LengthAtom { min, max };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(3, 3), vec![1]);
+        assert_eq!(matches(4, usize::MAX), vec![2, 3]);
+        assert_eq!(matches(0, 2), vec![0]);
+        assert_eq!(matches(6, 6), vec![3]);
+    }
+
+    #[test]
+    fn whitespace_atom_matches_preceding_whitespace() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+        let text = "a\u{a0}b c  d";
+        let tokens: Vec<_> = text
+            .char_indices()
+            .filter(|(_, c)| c.is_alphabetic())
+            .map(|(i, c)| {
+                let n_chars = text[..i].chars().count();
+
+                Token {
+                    word: Word::new_with_tags(WordId(c.to_string().into(), None), Vec::new()),
+                    char_span: (n_chars, n_chars + 1),
+                    byte_span: (i, i + 1),
+                    has_space_before: text[..i].ends_with(char::is_whitespace),
+                    chunks: Vec::new(),
+                    text,
+                    tagger: &tagger,
+                }
+            })
+            .collect();
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |whitespace: &str, empty_match| {
+            let atom = concrete::WhitespaceAtom {
+                matcher: Matcher {
+                    matcher: either::Left(either::Left(whitespace.to_string())),
+                    negate: false,
+                    case_sensitive: true,
+                    empty_always_false: true,
+                    empty_match,
+                },
+            };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches("\u{a0}", None), vec![1]);
+        assert_eq!(matches(" ", None), vec![2]);
+        assert_eq!(matches("  ", None), vec![3]);
+        assert_eq!(matches(" ", Some(true)), vec![0, 2]);
+    }
+
+    #[test]
+    fn pos_set_atom_matches_any_tag() {
+        let tagger = testing::tagger(&["NN", "NNS", "NNP", "VB", "DT"]);
+        let graph = MatchGraph::default();
+
+        let mut tokens = tokenize("the houses run", &tagger);
+        for (token, tags) in
+            tokens
+                .iter_mut()
+                .zip(&[vec!["DT"], vec!["NNS", "VB"], vec!["VB", "NN"]])
+        {
+            token.word.tags = tags
+                .iter()
+                .map(|x| WordData::new(WordId("".into(), None), tagger.id_tag(x)))
+                .collect();
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |tags: &[&str]| {
+            let atom = concrete::PosSetAtom {
+                tags: tags.iter().map(|x| x.to_string()).collect(),
+            };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(&["NN", "NNS", "NNP"]), vec![1, 2]);
+        assert_eq!(matches(&["DT"]), vec![0]);
+        assert!(matches(&[]).is_empty());
+    }
+
+    #[test]
+    fn sentence_end_atom_matches_terminal_punctuation() {
+        let tagger = testing::tagger(&["SENT_END"]);
+        let graph = MatchGraph::default();
+
+        let mut tokens = tokenize("Hi . Yes ! no", &tagger);
+        for i in [1, 3, 4] {
+            tokens[i].word.tags = vec![WordData::new(
+                WordId("".into(), None),
+                tagger.id_tag("SENT_END"),
+            )];
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let atom = concrete::SentenceEndAtom {};
+        assert_eq!(
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn first_word_atom_skips_leading_punctuation() {
+        let tagger = testing::tagger(&["SENT_END"]);
+        let graph = MatchGraph::default();
+
+        let mut tokens = tokenize("\" Hello world . ( Another one . - item 2", &tagger);
+        for i in [3, 7] {
+            tokens[i].word.tags = vec![WordData::new(
+                WordId("".into(), None),
+                tagger.id_tag("SENT_END"),
+            )];
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let atom = concrete::FirstWordAtom {};
+        assert_eq!(
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .map(|i| refs[i].word.text.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Hello", "Another", "item"]
+        );
+    }
+
+    #[test]
+    fn dictionary_atom_matches_known_words() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+        let tokens = tokenize("The cat saw a dgo", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let words: Arc<DefaultHashSet<String>> = Arc::new(
+            ["the", "cat", "saw", "a", "dog"]
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+        );
+
+        let matches = |case_sensitive| {
+            let atom = concrete::DictionaryAtom {
+                matcher: DictionaryMatcher {
+                    words: words.clone(),
+                    case_sensitive,
+                },
+            };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(false), vec![0, 1, 2, 3]);
+        assert_eq!(matches(true), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn feature_matcher_matches_subset_of_features() {
+        let matcher = |features: &[(&str, &str)]| FeatureMatcher {
+            features: features
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+
+        assert_eq!(
+            FeatureMatcher::parse("NOUN:gender=fem:number=plur").collect::<Vec<_>>(),
+            vec![("gender", "fem"), ("number", "plur")]
+        );
+
+        let tag = "NOUN:case=nom:gender=fem:number=plur";
+        assert!(matcher(&[("number", "plur")]).is_match(tag));
+        assert!(matcher(&[("gender", "fem"), ("number", "plur")]).is_match(tag));
+        assert!(!matcher(&[("gender", "fem"), ("number", "sing")]).is_match(tag));
+        assert!(!matcher(&[("tense", "past")]).is_match(tag));
+        assert!(matcher(&[]).is_match(tag));
+
+        assert!(matcher(&[("Number", "Sing")]).is_match("Gender=Masc|Number=Sing"));
+    }
+
+    #[test]
+    fn feature_atom_matches_any_tag() {
+        let tagger = testing::tagger(&[
+            "ADJ:number=sing",
+            "ADJ:number=plur",
+            "DET:gender=fem:number=sing",
+        ]);
+        let graph = MatchGraph::default();
+
+        let mut tokens = tokenize("die großen", &tagger);
+        for (token, tags) in tokens.iter_mut().zip(&[
+            vec!["DET:gender=fem:number=sing"],
+            vec!["ADJ:number=sing", "ADJ:number=plur"],
+        ]) {
+            token.word.tags = tags
+                .iter()
+                .map(|x| WordData::new(WordId("".into(), None), tagger.id_tag(x)))
+                .collect();
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |key: &str, value: &str| {
+            let atom = concrete::FeatureAtom {
+                matcher: FeatureMatcher {
+                    features: vec![(key.to_string(), value.to_string())],
+                },
+            };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches("number", "sing"), vec![0, 1]);
+        assert_eq!(matches("number", "plur"), vec![1]);
+        assert_eq!(matches("gender", "fem"), vec![0]);
+    }
+
+    #[test]
+    fn unknown_word_atom_matches_unrecognized_tokens() {
+        let tagger = testing::tagger(&["NN"]);
+        let graph = MatchGraph::default();
+
+        let mut tokens = tokenize("the blorf cat", &tagger);
+        for (token, tag) in tokens.iter_mut().zip(&["NN", "UNKNOWN", "NN"]) {
+            token.word.tags = vec![WordData::new(WordId("".into(), None), tagger.id_tag(tag))];
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let atom = concrete::UnknownWordAtom {};
+        assert_eq!(
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert!(refs[0].is_known());
+    }
+
+    #[test]
+    fn group_feature_atom_checks_subject_verb_agreement() {
+        let tagger = testing::tagger(&[
+            "DET:number=sing",
+            "NOUN:number=sing",
+            "NOUN:number=plur",
+            "VERB:number=sing",
+            "VERB:number=plur",
+        ]);
+
+        let pos_atom = |prefix: &str| -> Atom {
+            concrete::PosSetAtom {
+                tags: [
+                    format!("{}:number=sing", prefix),
+                    format!("{}:number=plur", prefix),
+                ]
+                .iter()
+                .cloned()
+                .collect(),
+            }
+            .into()
+        };
+
+        // an optional determiner and a noun followed by a verb which does not agree in number with the noun
+        let mut pattern = composition(vec![
+            pos_atom("DET"),
+            pos_atom("NOUN"),
+            AndAtom {
+                atoms: vec![
+                    pos_atom("VERB"),
+                    NotAtom {
+                        atom: Box::new(
+                            GroupFeatureAtom {
+                                group: 2,
+                                feature: "number".into(),
+                            }
+                            .into(),
+                        ),
+                    }
+                    .into(),
+                ],
+            }
+            .into(),
+        ]);
+        pattern.parts[0].quantifier = Quantifier { min: 0, max: 1 };
+
+        let is_error = |text, tags: &[&str]| {
+            let mut tokens = tokenize(text, &tagger);
+            for (token, tag) in tokens.iter_mut().zip(tags) {
+                token.word.tags = tag
+                    .split(' ')
+                    .map(|x| WordData::new(WordId("".into(), None), tagger.id_tag(x)))
+                    .collect();
+            }
+            let refs: Vec<_> = tokens.iter().collect();
+            pattern.apply(&refs, 0).is_some()
+        };
+
+        let (det, noun_sing, noun_plur) =
+            ("DET:number=sing", "NOUN:number=sing", "NOUN:number=plur");
+        let (verb_sing, verb_plur) = ("VERB:number=sing", "VERB:number=plur");
+
+        assert!(!is_error("the dog barks", &[det, noun_sing, verb_sing]));
+        assert!(is_error("the dog bark", &[det, noun_sing, verb_plur]));
+        assert!(!is_error("dogs bark", &[noun_plur, verb_plur]));
+        assert!(is_error("dogs barks", &[noun_plur, verb_sing]));
+        // ambiguous verb forms agree with either number
+        assert!(!is_error(
+            "dogs put",
+            &[noun_plur, "VERB:number=sing VERB:number=plur"]
+        ));
+    }
+
+    #[test]
+    fn byte_atom_matches_raw_bytes() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+
+        let tokens = tokenize("clean st\u{7}ray café", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |matcher| {
+            let atom = concrete::ByteAtom { matcher };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(ByteMatcher::Range(0x00, 0x1f)), vec![1]);
+        assert_eq!(matches(ByteMatcher::Sequence(vec![0xc3, 0xa9])), vec![2]);
+        assert_eq!(matches(ByteMatcher::Sequence(b"st".to_vec())), vec![1]);
+        assert_eq!(matches(ByteMatcher::Sequence(Vec::new())), vec![0, 1, 2]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-en")]
+    fn number_word_atom_compares_numbers() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+
+        let tokens = tokenize("seven twenty-three 23 dogs", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |comparison| {
+            let atom = concrete::NumberWordAtom {
+                matcher: NumberWordMatcher {
+                    language: Language::English,
+                    comparison,
+                },
+            };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(None), vec![0, 1]);
+        assert_eq!(matches(Some(NumericComparison::GreaterThan(10.))), vec![1]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-en")]
+    fn date_atom_detects_invalid_dates() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+
+        let tokens = tokenize("on 31st of February or March 3rd 2021", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |valid| {
+            let atom = concrete::DateAtom {
+                language: Language::English,
+                valid,
+            };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(None), vec![1, 5]);
+        assert_eq!(matches(Some(false)), vec![1]);
+        assert_eq!(matches(Some(true)), vec![5]);
+    }
+
+    #[test]
+    fn casing_uses_unicode_case() {
+        let casings = |text| {
+            [Casing::Lower, Casing::Upper, Casing::Title, Casing::Mixed]
+                .iter()
+                .copied()
+                .filter(|x| x.is_match(text))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(casings("straße"), vec![Casing::Lower]);
+        assert_eq!(casings("ÉCOLE"), vec![Casing::Upper]);
+        assert_eq!(casings("Émile"), vec![Casing::Title]);
+        assert_eq!(casings("iPhone"), vec![Casing::Mixed]);
+        assert_eq!(casings("McDonald"), vec![Casing::Mixed]);
+        assert_eq!(casings("A"), vec![Casing::Upper, Casing::Title]);
+        // Greek and Cyrillic have case
+        assert_eq!(casings("Αθήνα"), vec![Casing::Title]);
+        assert_eq!(casings("москва"), vec![Casing::Lower]);
+        assert_eq!(casings("МОСКВА"), vec![Casing::Upper]);
+        // characters without case are ignored
+        assert_eq!(casings("COVID-19"), vec![Casing::Upper]);
+        assert_eq!(casings("東京Tower"), vec![Casing::Title]);
+        assert!(casings("東京").is_empty());
+        assert!(casings("42").is_empty());
+    }
+
+    #[test]
+    fn case_atom_matches_token_text() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+        let tokens = tokenize("the Émile ÉCOLE 42", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let matches = |casing| {
+            let atom = concrete::CaseAtom { casing };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches(Casing::Lower), vec![0]);
+        assert_eq!(matches(Casing::Title), vec![1]);
+        assert_eq!(matches(Casing::Upper), vec![2]);
+        assert!(matches(Casing::Mixed).is_empty());
+    }
+
+    #[test]
+    fn repeat_atom_matches_repeated_words() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+
+        let matches = |text, case_sensitive| {
+            let tokens = tokenize(text, &tagger);
+            let refs: Vec<_> = tokens.iter().collect();
+            let atom = concrete::RepeatAtom { case_sensitive };
+
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matches("the the cat", false), vec![1]);
+        assert_eq!(matches("The the cat", false), vec![1]);
+        assert!(matches("The the cat", true).is_empty());
+        assert!(matches("the cat the", false).is_empty());
+    }
+
+    #[test]
+    fn chunk_span_atom_matches_full_phrase() {
+        let tagger = Tagger::default();
+
+        let mut tokens = tokenize("gave the dog a bone .", &tagger);
+        for (token, chunk) in tokens
+            .iter_mut()
+            .zip(&["B-VP", "B-NP", "I-NP", "B-NP", "I-NP", "O"])
+        {
+            token.chunks = vec![chunk.to_string()];
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let span = |group| {
+            Atom::from(concrete::ChunkSpanAtom {
+                chunk: "NP".into(),
+                group,
+            })
+        };
+        let mut pattern = composition(vec![span(1), span(2), text_atom(".")]);
+        for part in pattern.parts[..2].iter_mut() {
+            part.quantifier = Quantifier {
+                min: 1,
+                max: usize::MAX,
+            };
+        }
+
+        assert!(pattern.apply(&refs, 0).is_none());
+        assert!(pattern.apply(&refs, 2).is_none());
+
+        let graph = pattern.apply(&refs, 1).unwrap();
+        assert_eq!(graph.by_index(1).char_span, (5, 12));
+        assert_eq!(graph.by_index(2).char_span, (13, 19));
+    }
+
+    #[test]
+    fn chunk_span_atom_continues_after_zero_width_token() {
+        let tagger = Tagger::default();
+
+        // the leading empty token has a zero-width span at the start of the text like SENT_START
+        let mut tokens = tokenize(" dog .", &tagger);
+        for (token, chunk) in tokens.iter_mut().zip(&["B-NP", "I-NP", "O"]) {
+            token.chunks = vec![chunk.to_string()];
+        }
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let mut pattern = composition(vec![
+            concrete::ChunkSpanAtom {
+                chunk: "NP".into(),
+                group: 1,
+            }
+            .into(),
+            text_atom("."),
+        ]);
+        pattern.parts[0].quantifier = Quantifier {
+            min: 1,
+            max: usize::MAX,
+        };
+
+        // the group continues after the zero-width token, whose span is not part of the group
+        let graph = pattern.apply(&refs, 0).unwrap();
+        assert_eq!(graph.by_index(1).char_span, (1, 4));
+    }
+
+    #[test]
+    fn serializes_deterministically() {
+        let build = || {
+            composition(vec![
+                concrete::PosSetAtom {
+                    tags: (0..50).map(|x| format!("TAG{}", x)).collect(),
+                }
+                .into(),
+                text_atom("a"),
+            ])
+        };
+
+        let bytes = bincode::serialize(&build()).unwrap();
+        for _ in 0..5 {
+            assert_eq!(bincode::serialize(&build()).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn can_describe_atoms() {
+        let atom: Atom = AndAtom {
+            atoms: vec![
+                OrAtom {
+                    atoms: vec![text_atom("the"), text_atom("a")],
+                }
+                .into(),
+                NotAtom {
+                    atom: Box::new(concrete::LengthAtom { min: 0, max: 3 }.into()),
+                }
+                .into(),
+            ],
+        }
+        .into();
+
+        assert_eq!(atom.describe(), "((\"the\" | \"a\") & !length(0..=3))");
+    }
+
+    #[test]
+    fn abbreviation_atom_matches_known_abbreviations() {
+        let tagger = Tagger::default();
+        let graph = MatchGraph::default();
+
+        let mut tokens = tokenize("see Fig . 3 and fig . 4 Fig", &tagger);
+        // attach the periods to the preceding word
+        tokens[2].has_space_before = false;
+        tokens[6].has_space_before = false;
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let atom = concrete::AbbreviationAtom {
+            abbreviations: Arc::new(["Fig.".to_string()].iter().cloned().collect()),
+        };
+
+        assert_eq!(
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn raw_text_includes_whitespace() {
+        let source = "a  ,  b ü";
+
+        assert_eq!(Group::new((0, 7)).raw_text(source), "a  ,  b");
+        assert_eq!(Group::new((3, 9)).raw_text(source), ",  b ü");
+        assert_eq!(Group::new((3, 3)).raw_text(source), "");
+    }
+
+    #[test]
+    fn group_equality_atom_detects_duplicate_words() {
+        let tagger = Tagger::default();
+
+        let any = || -> Atom { TrueAtom {}.into() };
+        let pattern = composition(vec![
+            any(),
+            any(),
+            AndAtom {
+                atoms: vec![GroupEqualityAtom { a: 1, b: 2 }.into(), any()],
+            }
+            .into(),
+        ]);
+
+        let tokens = tokenize("I saw the the cat", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let starts: Vec<_> = (0..refs.len())
+            .filter(|i| pattern.apply(&refs, *i).is_some())
+            .collect();
+        assert_eq!(starts, vec![2]);
+    }
+
+    #[test]
+    fn predicate_atom_calls_closure() {
+        let tagger = Tagger::default();
+
+        let pattern = composition(vec![
+            text_atom("the"),
+            PredicateAtom {
+                predicate: Box::new(|token: &Token, _: &MatchGraph| {
+                    token.word.text.as_ref().ends_with("ing")
+                }),
+            }
+            .into(),
+        ]);
+
+        let tokens = tokenize("the running the dog", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let starts: Vec<_> = (0..refs.len())
+            .filter(|i| pattern.apply(&refs, *i).is_some())
+            .collect();
+        assert_eq!(starts, vec![0]);
+        assert_eq!(pattern.parts[1].atom.describe(), "predicate");
+        assert!(bincode::serialize(&pattern).is_err());
+    }
+
+    #[test]
+    fn never_matches_zero_tokens() {
+        let tagger = Tagger::default();
+
+        let required = composition(vec![text_atom("a")]);
+        let mut optional = composition(vec![text_atom("a"), text_atom("b")]);
+        for part in optional.parts.iter_mut() {
+            part.quantifier = Quantifier { min: 0, max: 1 };
+        }
+        optional.can_stop_mask = vec![true; optional.parts.len()];
+
+        let empty: Vec<&Token> = Vec::new();
+        for pattern in &[&required, &optional] {
+            assert!(pattern.apply(&empty, 0).is_none());
+            assert!(pattern.find_all(&empty, true).is_empty());
         }
+
+        let tokens = tokenize("c b", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        assert!(optional.apply(&refs, 0).is_none());
+        assert!(optional.apply(&refs, 2).is_none());
+        let graph = optional.apply(&refs, 1).unwrap();
+        assert_eq!(graph.by_id(1).unwrap().char_span, (2, 2));
+        assert_eq!(graph.by_id(2).unwrap().char_span, (2, 3));
+    }
+
+    #[test]
+    fn within_group_atom_matches_tokens_of_group() {
+        let tagger = Tagger::default();
+
+        let tokens = tokenize("the big cat sat", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+        let id_to_idx: DefaultHashMap<_, _> = vec![(0, 0), (1, 1), (2, 2)].into_iter().collect();
+        // group 1 is "big cat", group 2 did not match
+        let mut empty = Group::new((11, 11));
+        empty.matched = false;
+        let graph = MatchGraph::new(
+            vec![Group::new((0, 15)), Group::new((4, 11)), empty],
+            &id_to_idx,
+            &refs,
+        );
+
+        let matches = |group| {
+            let atom = WithinGroupAtom { group };
+            (0..refs.len())
+                .filter(|i| atom.is_match(&refs, &graph, *i))
+                .map(|i| refs[i].word.text.as_ref())
+                .collect::<Vec<&str>>()
+        };
+
+        assert_eq!(matches(1), vec!["big", "cat"]);
+        assert!(matches(2).is_empty());
+        assert!(matches(3).is_empty());
+    }
+
+    #[cfg(any(feature = "regex", feature = "regex-pure"))]
+    fn regex_matcher(negate: bool, empty_always_false: bool) -> Matcher {
+        Matcher {
+            matcher: either::Right(SerializeRegex::new("a+", true, true).unwrap()),
+            negate,
+            case_sensitive: true,
+            empty_always_false,
+            empty_match: None,
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "regex", feature = "regex-pure"))]
+    fn regex_matcher_truth_table() {
+        let graph = MatchGraph::default();
+
+        for &(negate, empty_always_false, empty, match_, no_match) in &[
+            (false, false, false, true, false),
+            (true, false, true, false, true),
+            (false, true, false, true, false),
+            (true, true, false, false, true),
+        ] {
+            let matcher = regex_matcher(negate, empty_always_false);
+
+            assert_eq!(matcher.is_match("", &graph, None), empty);
+            assert_eq!(matcher.is_match("aaa", &graph, None), match_);
+            assert_eq!(matcher.is_match("b", &graph, None), no_match);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "regex", feature = "regex-pure"))]
+    fn empty_match_overrides_empty_behavior() {
+        let graph = MatchGraph::default();
+
+        let mut matcher = regex_matcher(false, true);
+        matcher.empty_match = Some(true);
+        assert!(matcher.is_match("", &graph, None));
+
+        let mut matcher = regex_matcher(true, false);
+        matcher.empty_match = Some(false);
+        assert!(!matcher.is_match("", &graph, None));
+        assert!(matcher.is_match("b", &graph, None));
+    }
+
+    #[test]
+    fn literal_is_none_if_matcher_matches_empty_input() {
+        let mut matcher = Matcher {
+            matcher: either::Left(either::Left("test".into())),
+            negate: false,
+            case_sensitive: true,
+            empty_always_false: true,
+            empty_match: None,
+        };
+        assert_eq!(matcher.literal(), Some("test"));
+
+        matcher.empty_match = Some(false);
+        assert_eq!(matcher.literal(), Some("test"));
+
+        matcher.empty_match = Some(true);
+        assert!(matcher.is_match("", &MatchGraph::default(), None));
+        assert_eq!(matcher.literal(), None);
     }
 }
@@ -1,3 +1,5 @@
+//! The engines which find the matches of a rule in a sequence of tokens.
+
 use crate::{types::*, utils::regex::SerializeRegex};
 use serde::{Deserialize, Serialize};
 pub mod composition;
@@ -12,49 +14,62 @@ pub struct TokenEngine {
 
 impl TokenEngine {
     fn get_match<'t>(&'t self, tokens: &'t [&'t Token], i: usize) -> Option<MatchGraph<'t>> {
-        if let Some(graph) = self.composition.apply(tokens, i) {
-            let mut blocked = false;
-
-            // TODO: cache / move to outer loop
-            for i in 0..tokens.len() {
-                for antipattern in &self.antipatterns {
-                    if let Some(anti_graph) = antipattern.apply(tokens, i) {
-                        let anti_start = anti_graph.by_index(0).char_span.0;
-                        let anti_end = anti_graph
-                            .by_index(anti_graph.groups().len() - 1)
-                            .char_span
-                            .1;
-
-                        let rule_start = graph.by_index(0).char_span.0;
-                        let rule_end = graph.by_index(graph.groups().len() - 1).char_span.1;
-
-                        if anti_start <= rule_end && rule_start <= anti_end {
-                            blocked = true;
-                            break;
-                        }
+        let graph = self.composition.apply(tokens, i)?;
+
+        if self.is_blocked(tokens, &graph) {
+            None
+        } else {
+            Some(graph)
+        }
+    }
+
+    /// Whether one of the antipatterns matches somewhere overlapping with the match `graph`.
+    fn is_blocked<'t>(&'t self, tokens: &'t [&'t Token], graph: &MatchGraph) -> bool {
+        // reused for all antipattern matches to avoid allocating a new graph for each position
+        let mut anti_graph = MatchGraph::default();
+
+        // TODO: cache / move to outer loop
+        for i in 0..tokens.len() {
+            for antipattern in &self.antipatterns {
+                if antipattern.apply_into(tokens, i, &mut anti_graph) {
+                    let anti_start = anti_graph.by_index(0).char_span.0;
+                    let anti_end = anti_graph
+                        .by_index(anti_graph.groups().len() - 1)
+                        .char_span
+                        .1;
+
+                    let rule_start = graph.by_index(0).char_span.0;
+                    let rule_end = graph.by_index(graph.groups().len() - 1).char_span.1;
+
+                    if anti_start <= rule_end && rule_start <= anti_end {
+                        return true;
                     }
                 }
-                if blocked {
-                    break;
-                }
-            }
-
-            if !blocked {
-                return Some(graph);
             }
         }
 
-        None
+        false
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum Engine {
     Token(TokenEngine),
-    Text(SerializeRegex, DefaultHashMap<usize, usize>),
+    Text(
+        SerializeRegex,
+        #[serde(serialize_with = "crate::utils::sorted::map")] DefaultHashMap<usize, usize>,
+    ),
 }
 
 impl Engine {
+    /// Literal token texts of which at least one must occur for this engine to find a match, if they can be determined.
+    pub fn required_literals(&self) -> Option<Vec<&str>> {
+        match &self {
+            Engine::Token(engine) => engine.composition.required_literals(),
+            Engine::Text(_, _) => None,
+        }
+    }
+
     pub fn get_matches<'t>(
         &'t self,
         tokens: &'t [&'t Token],
@@ -65,10 +80,12 @@ impl Engine {
 
         match &self {
             Engine::Token(engine) => {
-                let mut graph_info: Vec<_> = (0..tokens.len())
+                let mut graph_info: Vec<_> = engine
+                    .composition
+                    .find_all(tokens, true)
                     .into_iter()
-                    .filter_map(|i| {
-                        if let Some(graph) = engine.get_match(&tokens, i) {
+                    .filter_map(|graph| {
+                        if !engine.is_blocked(tokens, &graph) {
                             let start_group = graph
                                 .by_id(start)
                                 .unwrap_or_else(|| panic!("group must exist in graph: {}", start));
@@ -96,34 +113,79 @@ impl Engine {
                 }
             }
             Engine::Text(regex, id_to_idx) => {
-                // this is the entire text, NOT the text of one token
-                let text = tokens[0].text;
-
-                let mut byte_to_char_idx: DefaultHashMap<usize, usize> = text
-                    .char_indices()
-                    .enumerate()
-                    .map(|(ci, (bi, _))| (bi, ci))
-                    .collect();
-                byte_to_char_idx.insert(text.len(), byte_to_char_idx.len());
+                graphs.extend(Engine::text_matches(regex, id_to_idx, tokens));
+            }
+        }
 
-                graphs.extend(regex.captures_iter(text).map(|captures| {
-                    let mut groups = Vec::new();
-                    for group in captures.iter_pos() {
-                        if let Some(group) = group {
-                            let start = *byte_to_char_idx.get(&group.0).unwrap();
-                            let end = *byte_to_char_idx.get(&group.1).unwrap();
+        graphs
+    }
 
-                            groups.push(Group::new((start, end)));
-                        } else {
-                            groups.push(Group::new((0, 0)));
-                        }
-                    }
+    /// Gets only the first match. Does not search for further matches after one is found.
+    pub fn get_first_match<'t>(
+        &'t self,
+        tokens: &'t [&'t Token],
+        start: usize,
+        end: usize,
+    ) -> Option<MatchGraph<'t>> {
+        self.iter_matches(tokens, start, end).next()
+    }
 
-                    MatchGraph::new(groups, id_to_idx, tokens)
-                }));
+    /// Lazily finds matches ordered by their start. In contrast to [get_matches][Engine::get_matches],
+    /// overlapping matches are not removed.
+    pub fn iter_matches<'t>(
+        &'t self,
+        tokens: &'t [&'t Token],
+        start: usize,
+        end: usize,
+    ) -> Box<dyn Iterator<Item = MatchGraph<'t>> + 't> {
+        match &self {
+            Engine::Token(engine) => Box::new((0..tokens.len()).filter_map(move |i| {
+                let graph = engine.get_match(tokens, i)?;
+
+                graph
+                    .by_id(start)
+                    .unwrap_or_else(|| panic!("group must exist in graph: {}", start));
+                graph
+                    .by_id(end - 1)
+                    .unwrap_or_else(|| panic!("group must exist in graph: {}", end - 1));
+
+                Some(graph)
+            })),
+            Engine::Text(regex, id_to_idx) => {
+                Box::new(Engine::text_matches(regex, id_to_idx, tokens))
             }
         }
+    }
 
-        graphs
+    fn text_matches<'t>(
+        regex: &'t SerializeRegex,
+        id_to_idx: &'t DefaultHashMap<usize, usize>,
+        tokens: &'t [&'t Token],
+    ) -> impl Iterator<Item = MatchGraph<'t>> {
+        // this is the entire text, NOT the text of one token
+        let text = tokens[0].text;
+
+        let mut byte_to_char_idx: DefaultHashMap<usize, usize> = text
+            .char_indices()
+            .enumerate()
+            .map(|(ci, (bi, _))| (bi, ci))
+            .collect();
+        byte_to_char_idx.insert(text.len(), byte_to_char_idx.len());
+
+        regex.captures_pos(text).map(move |captures| {
+            let mut groups = Vec::new();
+            for group in captures {
+                if let Some(group) = group {
+                    let start = *byte_to_char_idx.get(&group.0).unwrap();
+                    let end = *byte_to_char_idx.get(&group.1).unwrap();
+
+                    groups.push(Group::new((start, end)));
+                } else {
+                    groups.push(Group::default());
+                }
+            }
+
+            MatchGraph::new(groups, id_to_idx, tokens)
+        })
     }
 }
@@ -4,7 +4,6 @@ use crate::{
     tokenizer::Tokenizer,
     utils::{self, regex::SerializeRegex},
 };
-use onig::Captures;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -17,6 +16,7 @@ impl std::cmp::PartialEq for Suggestion {
     }
 }
 
+/// A case transformation applied to the text of a group.
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Conversion {
     Nop,
@@ -107,6 +107,9 @@ impl PosReplacer {
     }
 }
 
+/// Inserts the text of the group with the given ID into a suggestion. The text can be transformed
+/// by inflecting it to another part-of-speech tag, replacing a regex and converting its case (in that order).
+/// The regex replacement can refer to capture groups with `$1`, `$2` etc. e. g. to rewrite the suffix of a word.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Match {
     pub(crate) id: usize,
@@ -129,9 +132,7 @@ impl Match {
         };
 
         text = if let Some((regex, replacement)) = &self.regex_replacer {
-            regex.replace_all(&text, |caps: &Captures| {
-                utils::dollar_replace(replacement.to_string(), caps)
-            })
+            regex.replace_all_dollar(&text, replacement)
         } else {
             text
         };
@@ -151,12 +152,29 @@ pub enum SynthesizerPart {
     Match(Match),
 }
 
+/// Generates a suggestion or message from literal text and the groups of a match.
+///
+/// Unless the output starts with a case conversion, it is capitalized if the replaced text starts the text
+/// or (if `use_titlecase_adjust` is set) starts with an uppercase letter. If `sentence_case` is set, the output
+/// is capitalized if and only if the replaced text starts any sentence, e. g. for a replacement inserting a new first word.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Synthesizer {
     pub(crate) use_titlecase_adjust: bool,
+    pub(crate) sentence_case: bool,
     pub(crate) parts: Vec<SynthesizerPart>,
 }
 
+/// Whether the token at `index` is the first token of a sentence i. e. it is preceded by the sentence start token,
+/// by a token ending a sentence or by nothing.
+fn is_sentence_start(tokens: &[&Token], index: usize) -> bool {
+    index == 0 || {
+        let prev = tokens[index - 1];
+
+        prev.char_span.0 == prev.char_span.1
+            || prev.word.tags.iter().any(|x| x.pos.as_ref() == "SENT_END")
+    }
+}
+
 impl Synthesizer {
     pub fn apply(
         &self,
@@ -186,12 +204,23 @@ impl Synthesizer {
         // if the suggestion does not start with a case conversion match, make it title case if:
         // * at sentence start
         // * the replaced text is title case
+        // if `sentence_case` is set, only the sentence start is considered
         let make_uppercase = !starts_with_conversion
             && graph.groups()[graph.get_index(start).unwrap()..]
                 .iter()
                 .find(|x| !x.tokens(graph.tokens()).is_empty())
                 .map(|group| {
                     let first_token = group.tokens(graph.tokens())[0];
+
+                    if self.sentence_case {
+                        // tokens are sorted by position so the index can be found with a binary search
+                        let index = graph
+                            .tokens()
+                            .partition_point(|x| x.char_span.0 < first_token.char_span.0);
+                        return first_token.byte_span.0 == 0
+                            || is_sentence_start(graph.tokens(), index);
+                    }
+
                     (self.use_titlecase_adjust
                         && first_token
                             .word
@@ -214,3 +243,165 @@ impl Synthesizer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rule::engine::composition::Group, tokenizer::tag::Tagger, utils::testing::tokenize,
+    };
+
+    fn group(id: usize, conversion: Conversion) -> SynthesizerPart {
+        SynthesizerPart::Match(Match {
+            id,
+            conversion,
+            pos_replacer: None,
+            regex_replacer: None,
+        })
+    }
+
+    #[test]
+    fn can_convert_case_of_groups() {
+        let tagger = Tagger::default();
+        let tokenizer = Tokenizer::default();
+        let tokens = tokenize("we met john SMITH", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let id_to_idx = (0..3).map(|i| (i, i)).collect();
+        let graph = MatchGraph::new(
+            vec![
+                Group::new((7, 17)),
+                Group::new((7, 11)),
+                Group::new((12, 17)),
+            ],
+            &id_to_idx,
+            &refs,
+        );
+
+        let synthesize = |parts| {
+            Synthesizer {
+                use_titlecase_adjust: false,
+                sentence_case: false,
+                parts,
+            }
+            .apply(&graph, &tokenizer, 1, 3)
+            .unwrap()
+        };
+
+        assert_eq!(synthesize(vec![group(1, Conversion::StartUpper)]), "John");
+        assert_eq!(synthesize(vec![group(1, Conversion::AllUpper)]), "JOHN");
+        assert_eq!(synthesize(vec![group(2, Conversion::StartLower)]), "sMITH");
+        assert_eq!(
+            synthesize(vec![
+                group(1, Conversion::StartUpper),
+                SynthesizerPart::Text(" ".into()),
+                group(2, Conversion::AllLower),
+            ]),
+            "John smith"
+        );
+        assert_eq!(synthesize(vec![group(1, Conversion::Nop)]), "john");
+    }
+
+    #[test]
+    #[cfg(any(feature = "regex", feature = "regex-pure"))]
+    fn can_rewrite_suffix_with_captures() {
+        let tagger = Tagger::default();
+        let tokenizer = Tokenizer::default();
+        let tokens = tokenize("he walking", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let id_to_idx = (0..2).map(|i| (i, i)).collect();
+        let graph = MatchGraph::new(
+            vec![Group::new((3, 10)), Group::new((3, 10))],
+            &id_to_idx,
+            &refs,
+        );
+
+        let regex = SerializeRegex::new(r"(\w+)ing", false, true).unwrap();
+        assert_eq!(
+            regex.captures("walking"),
+            Some(vec![Some("walking"), Some("walk")])
+        );
+        assert_eq!(regex.group_at("walking", 1), Some("walk"));
+        assert_eq!(regex.captures("walked"), None);
+
+        let synthesizer = Synthesizer {
+            use_titlecase_adjust: false,
+            sentence_case: false,
+            parts: vec![SynthesizerPart::Match(Match {
+                id: 1,
+                conversion: Conversion::Nop,
+                pos_replacer: None,
+                regex_replacer: Some((regex, "$1ed".into())),
+            })],
+        };
+
+        assert_eq!(
+            synthesizer.apply(&graph, &tokenizer, 1, 2).unwrap(),
+            "walked"
+        );
+    }
+
+    #[test]
+    fn can_capitalize_at_sentence_start() {
+        let tagger = Tagger::default();
+        let tokenizer = Tokenizer::default();
+        let tokens = tokenize("so We met", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let id_to_idx = (0..3).map(|i| (i, i)).collect();
+        let graph = MatchGraph::new(
+            vec![Group::new((0, 5)), Group::new((0, 2)), Group::new((3, 5))],
+            &id_to_idx,
+            &refs,
+        );
+
+        let synthesize = |id| {
+            Synthesizer {
+                use_titlecase_adjust: true,
+                sentence_case: true,
+                parts: vec![
+                    SynthesizerPart::Text("well ".into()),
+                    group(id, Conversion::Nop),
+                ],
+            }
+            .apply(&graph, &tokenizer, id, id + 1)
+            .unwrap()
+        };
+
+        assert_eq!(synthesize(1), "Well so");
+        assert_eq!(synthesize(2), "well We");
+    }
+
+    #[test]
+    fn only_capitalizes_later_sentence_starts_with_sentence_case() {
+        let tagger = Tagger::default();
+        let tokenizer = Tokenizer::default();
+        // the empty token marks a sentence boundary
+        let tokens = tokenize("done  so we", &tagger);
+        let refs: Vec<_> = tokens.iter().collect();
+
+        let id_to_idx = (0..2).map(|i| (i, i)).collect();
+        let graph = MatchGraph::new(
+            vec![Group::new((6, 8)), Group::new((6, 8))],
+            &id_to_idx,
+            &refs,
+        );
+
+        let synthesize = |sentence_case| {
+            Synthesizer {
+                use_titlecase_adjust: true,
+                sentence_case,
+                parts: vec![
+                    SynthesizerPart::Text("well ".into()),
+                    group(1, Conversion::Nop),
+                ],
+            }
+            .apply(&graph, &tokenizer, 1, 2)
+            .unwrap()
+        };
+
+        assert_eq!(synthesize(true), "Well so");
+        assert_eq!(synthesize(false), "well so");
+    }
+}
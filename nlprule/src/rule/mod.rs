@@ -3,7 +3,7 @@
 use crate::types::*;
 use crate::{
     filter::{Filter, Filterable},
-    tokenizer::{finalize, Tokenizer},
+    tokenizer::{finalize, AsTokens, Tokenizer},
     utils,
 };
 use log::{error, info, warn};
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 pub(crate) mod disambiguation;
-pub(crate) mod engine;
+pub mod engine;
 pub(crate) mod grammar;
 
 use engine::Engine;
@@ -19,6 +19,19 @@ use engine::Engine;
 pub(crate) use engine::composition::MatchGraph;
 pub use grammar::Example;
 
+/// One part of the pattern of a [Rule], see [pattern][Rule::pattern].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternPart {
+    /// A short human-readable description of what the part matches.
+    pub description: String,
+    /// The minimum number of consecutive tokens the part must match.
+    pub min: usize,
+    /// The maximum number of consecutive tokens the part can match.
+    pub max: usize,
+    /// Whether the tokens matched by this part are part of the match (e. g. not the case for skipped tokens).
+    pub visible: bool,
+}
+
 /// A disambiguation rule.
 /// Changes the information associcated with one or more tokens if it matches.
 /// Sourced from LanguageTool. An example of how a simple rule might look in the original XML format:
@@ -143,7 +156,7 @@ impl DisambiguationRule {
             };
 
             let tokens_before =
-                tokenizer.disambiguate_up_to_id(tokenizer.tokenize(text), Some(&self.id));
+                tokenizer.disambiguate_up_to_id(tokenizer.tokenize(text), Some(&self.id), true);
             let finalized = finalize(tokens_before.clone());
             let changes = self.apply(&finalized, tokenizer);
             let mut tokens_after = tokens_before.clone();
@@ -239,6 +252,10 @@ pub struct Rule {
     pub(crate) start: usize,
     pub(crate) end: usize,
     pub(crate) on: bool,
+    pub(crate) first_match_only: bool,
+    pub(crate) language: Option<String>,
+    pub(crate) confidence: Option<f32>,
+    pub(crate) priority: i32,
     pub(crate) url: Option<String>,
     pub(crate) short: Option<String>,
     pub(crate) name: String,
@@ -278,6 +295,40 @@ impl Rule {
         self.on = on;
     }
 
+    /// Get whether this rule stops after the first match per sentence instead of finding all matches.
+    pub fn first_match_only(&self) -> bool {
+        self.first_match_only
+    }
+
+    /// Set whether this rule should stop after the first match per sentence.
+    /// Useful for expensive rules where it only matters whether they trigger in a sentence at all.
+    pub fn set_first_match_only(&mut self, first_match_only: bool) {
+        self.first_match_only = first_match_only;
+    }
+
+    /// Gets the language code (e. g. "de") this rule is restricted to if there is one.
+    /// Rules without a language apply to every language.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Gets the confidence (between zero and one) that suggestions of this rule are correct if there is one.
+    /// Rules without a confidence should be treated as having a confidence of 1.0.
+    pub fn confidence(&self) -> Option<f32> {
+        self.confidence
+    }
+
+    /// Gets the priority of this rule (zero by default). If suggestions overlap, the one from the rule
+    /// with higher priority is kept, see [OverlapStrategy][crate::rules::OverlapStrategy].
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Sets the priority of this rule.
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
     /// Gets a human-readable name of this rule.
     pub fn name(&self) -> &str {
         &self.name
@@ -298,70 +349,171 @@ impl Rule {
         self.category_type.as_deref()
     }
 
-    pub(crate) fn apply(&self, tokens: &[Token], tokenizer: &Tokenizer) -> Vec<Suggestion> {
+    /// Gets the parts of the token pattern of this rule e. g. to display its structure.
+    /// Returns `None` if the rule matches a regex on the text instead of a token pattern.
+    pub fn pattern(&self) -> Option<Vec<PatternPart>> {
+        match &self.engine {
+            Engine::Token(engine) => Some(
+                engine
+                    .composition
+                    .parts
+                    .iter()
+                    .map(|part| PatternPart {
+                        description: part.atom.describe(),
+                        min: part.quantifier.min,
+                        max: part.quantifier.max,
+                        visible: part.visible,
+                    })
+                    .collect(),
+            ),
+            Engine::Text(_, _) => None,
+        }
+    }
+
+    /// Serializes this rule on its own e. g. to distribute it independently of a rule set.
+    /// Load it with [from_bytes][Rule::from_bytes] and add it to a set with [push][crate::Rules::push].
+    ///
+    /// The format is the same bincode encoding used for [Rules][crate::Rules] binaries, so the same caveats apply:
+    /// it is not self-describing and not stable across nlprule versions. A rule must be loaded by the same version
+    /// of nlprule it was serialized with and used with a tokenizer built from the same tag set,
+    /// since part-of-speech matchers and word IDs refer to the tagger by index.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Deserializes a rule serialized with [to_bytes][Rule::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize(bytes)
+    }
+
+    /// Finds all matches of this rule in the given tokens.
+    /// The matches are owned so they can outlive the tokens.
+    pub fn matches(&self, tokens: &[Token]) -> Vec<owned::MatchGraph> {
         let refs: Vec<&Token> = tokens.iter().collect();
-        let mut suggestions = Vec::new();
 
-        for graph in self.engine.get_matches(&refs, self.start, self.end) {
-            let start_group = graph
-                .by_id(self.start)
-                .unwrap_or_else(|| panic!("{} group must exist in graph: {}", self.id, self.start));
-            let end_group = graph.by_id(self.end - 1).unwrap_or_else(|| {
-                panic!("{} group must exist in graph: {}", self.id, self.end - 1)
-            });
-
-            let replacements: Vec<String> = self
-                .suggesters
+        self.engine
+            .get_matches(&refs, self.start, self.end)
+            .iter()
+            .map(|graph| graph.to_owned_graph())
+            .collect()
+    }
+
+    /// Checks whether the pattern of this rule matches anywhere in the given tokens.
+    /// Cheaper than [apply][Rule::apply] since no message or replacements are generated.
+    pub(crate) fn is_match(&self, tokens: &[Token]) -> bool {
+        let refs: Vec<&Token> = tokens.iter().collect();
+
+        self.engine
+            .get_first_match(&refs, self.start, self.end)
+            .is_some()
+    }
+
+    /// Computes the suggestions of this rule. `sentence_starts` are the char indices at which the sentences
+    /// of the text start, they are only needed if the rule has [first_match_only][Rule::first_match_only] set.
+    pub(crate) fn apply(
+        &self,
+        tokens: &[Token],
+        sentence_starts: &[usize],
+        tokenizer: &Tokenizer,
+    ) -> Vec<Suggestion> {
+        let refs: Vec<&Token> = tokens.iter().collect();
+
+        if self.first_match_only {
+            let mut done_sentence = None;
+
+            // matches are in order, so the sentence of a match is done once one of its matches produced a suggestion.
+            // a match for which no suggestion can be generated does not count as the first match
+            self.engine
+                .iter_matches(&refs, self.start, self.end)
+                .filter_map(|graph| {
+                    let start = graph.by_id(self.start)?.char_span.0;
+                    let sentence = sentence_starts.partition_point(|x| *x <= start);
+
+                    if done_sentence == Some(sentence) {
+                        return None;
+                    }
+
+                    let suggestion = self.suggestion_for(&graph, tokens, tokenizer)?;
+                    done_sentence = Some(sentence);
+                    Some(suggestion)
+                })
+                .collect()
+        } else {
+            self.engine
+                .get_matches(&refs, self.start, self.end)
                 .iter()
-                .filter_map(|x| x.apply(&graph, tokenizer, self.start, self.end))
-                .collect();
+                .filter_map(|graph| self.suggestion_for(graph, tokens, tokenizer))
+                .collect()
+        }
+    }
 
-            let start = if replacements
+    /// Generates the suggestion for one match of this rule. Returns `None` if no replacement can be generated.
+    fn suggestion_for(
+        &self,
+        graph: &MatchGraph,
+        tokens: &[Token],
+        tokenizer: &Tokenizer,
+    ) -> Option<Suggestion> {
+        let start_group = graph
+            .by_id(self.start)
+            .unwrap_or_else(|| panic!("{} group must exist in graph: {}", self.id, self.start));
+        let end_group = graph
+            .by_id(self.end - 1)
+            .unwrap_or_else(|| panic!("{} group must exist in graph: {}", self.id, self.end - 1));
+
+        let replacements: Vec<String> = self
+            .suggesters
+            .iter()
+            .filter_map(|x| x.apply(graph, tokenizer, self.start, self.end))
+            .collect();
+
+        let start = if replacements
+            .iter()
+            .all(|x| utils::no_space_chars().chars().any(|c| x.starts_with(c)))
+        {
+            let first_token = graph.groups()[graph.get_index(self.start).unwrap()..]
                 .iter()
-                .all(|x| utils::no_space_chars().chars().any(|c| x.starts_with(c)))
-            {
-                let first_token = graph.groups()[graph.get_index(self.start).unwrap()..]
-                    .iter()
-                    .find(|x| !x.tokens(graph.tokens()).is_empty())
-                    .unwrap()
-                    .tokens(graph.tokens())[0];
+                .find(|x| !x.tokens(graph.tokens()).is_empty())
+                .unwrap()
+                .tokens(graph.tokens())[0];
 
-                let idx = tokens
-                    .iter()
-                    .position(|x| std::ptr::eq(x, first_token))
-                    .unwrap_or(0);
+            let idx = tokens
+                .iter()
+                .position(|x| std::ptr::eq(x, first_token))
+                .unwrap_or(0);
 
-                if idx > 0 {
-                    tokens[idx - 1].char_span.1
-                } else {
-                    start_group.char_span.0
-                }
+            if idx > 0 {
+                tokens[idx - 1].char_span.1
             } else {
                 start_group.char_span.0
-            };
-            let end = end_group.char_span.1;
-
-            // fix e. g. "Super , dass"
-            let replacements: Vec<String> = replacements
-                .into_iter()
-                .map(|x| utils::fix_nospace_chars(&x))
-                .collect();
-
-            if !replacements.is_empty() {
-                suggestions.push(Suggestion {
-                    message: self
-                        .message
-                        .apply(&graph, tokenizer, self.start, self.end)
-                        .expect("Rules must have a message."),
-                    source: self.id.to_string(),
-                    start,
-                    end,
-                    replacements,
-                });
             }
+        } else {
+            start_group.char_span.0
+        };
+        let end = end_group.char_span.1;
+
+        // fix e. g. "Super , dass"
+        let replacements: Vec<String> = replacements
+            .into_iter()
+            .map(|x| utils::fix_nospace_chars(&x))
+            .collect();
+
+        if replacements.is_empty() {
+            return None;
         }
 
-        suggestions
+        Some(Suggestion {
+            message: self
+                .message
+                .apply(graph, tokenizer, self.start, self.end)
+                .expect("Rules must have a message."),
+            source: self.id.to_string(),
+            start,
+            end,
+            replacements,
+            confidence: self.confidence,
+            sentence_index: 0,
+        })
     }
 
     /// Grammar rules always have at least one example associated with them.
@@ -372,7 +524,7 @@ impl Rule {
         for test in self.examples.iter() {
             let tokens = finalize(tokenizer.disambiguate(tokenizer.tokenize(&test.text())));
             info!("Tokens: {:#?}", tokens);
-            let suggestions = self.apply(&tokens, tokenizer);
+            let suggestions = self.apply(&tokens, &tokens.sentence_starts(tokenizer), tokenizer);
 
             let pass = if suggestions.len() > 1 {
                 false
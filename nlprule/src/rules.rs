@@ -1,27 +1,41 @@
 //! Sets of grammatical error correction rules.
 
-use crate::tokenizer::Tokenizer;
+use crate::tokenizer::{sentence_range, AsTokens, Tokenized, Tokenizer};
 use crate::types::*;
-use crate::utils::parallelism::MaybeParallelRefIterator;
+use crate::utils::{self, bloom::BloomFilter, parallelism::MaybeParallelRefIterator};
 use crate::{rule::Rule, tokenizer::finalize};
+use log::warn;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "profile")]
+use std::{collections::HashMap, time::Duration};
 use std::{
+    collections::HashSet,
     fs::File,
     io::{BufReader, Read},
     path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
+use thiserror::Error;
 
 /// Options for a rule set.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RulesOptions {
     /// Whether to allow errors while constructing the rules.
     pub allow_errors: bool,
-    /// Grammar Rule IDs to use in this set.
+    /// Grammar Rule IDs to use in this set. Rules with other IDs are not compiled at all.
     #[serde(default)]
     pub ids: Vec<String>,
     /// Grammar Rule IDs to ignore in this set.
     #[serde(default)]
     pub ignore_ids: Vec<String>,
+    /// Whether to match all token text case-insensitively, regardless of the `case_sensitive` attributes of the rules.
+    /// Part-of-speech tags are still matched case-sensitively.
+    #[serde(default)]
+    pub case_insensitive: bool,
 }
 
 impl Default for RulesOptions {
@@ -30,18 +44,115 @@ impl Default for RulesOptions {
             allow_errors: true,
             ids: Vec::new(),
             ignore_ids: Vec::new(),
+            case_insensitive: false,
         }
     }
 }
 
+impl RulesOptions {
+    /// Sets the IDs of the rules to use in this set.
+    pub fn with_ids<I, S>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ids = ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the IDs of the rules to ignore in this set.
+    pub fn ignoring<I, S>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_ids = ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether to allow errors while constructing the rules.
+    pub fn allow_errors(mut self, allow_errors: bool) -> Self {
+        self.allow_errors = allow_errors;
+        self
+    }
+
+    /// Sets whether to match all token text case-insensitively.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+}
+
+/// How to resolve suggestions which overlap with each other.
+/// Except for [LongestWins][OverlapStrategy::LongestWins], the suggestion from the rule with the higher
+/// [priority][Rule::priority] always wins, the strategy decides between rules with the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlapStrategy {
+    /// Keep the suggestion which starts first. If two suggestions start at the same position, the one from the rule
+    /// which comes later in [Rules::rules] wins.
+    #[default]
+    FirstWins,
+    /// Keep the suggestion with the larger span. Ties are broken by rule priority, then by start position.
+    LongestWins,
+    /// Keep the suggestion with the larger span, like [LongestWins][OverlapStrategy::LongestWins] but only between rules
+    /// with the same priority. If the spans have the same length, the suggestion from the rule which comes first in
+    /// [Rules::rules] wins, then the one which starts first.
+    PriorityWins,
+}
+
 /// A set of grammatical error correction rules.
+///
+/// No state derived from the rules is cached, so changes to the set (e. g. with [push][Rules::push]
+/// or [remove_by_id][Rules::remove_by_id]) take effect on the next call without invalidating anything.
 #[derive(Serialize, Deserialize, Default)]
 pub struct Rules {
     pub(crate) rules: Vec<Rule>,
+    #[serde(skip)]
+    pub(crate) post_processors: Vec<PostProcessor>,
+    #[serde(skip)]
+    pub(crate) synonym_provider: Option<Arc<dyn SynonymProvider>>,
+}
+
+/// A function adjusting a suggestion after it is generated by a rule, see [add_post_processor][Rules::add_post_processor].
+pub type PostProcessor = Arc<dyn Fn(&mut Suggestion) + Send + Sync>;
+
+/// A thesaurus used to offer synonyms as additional replacements, see [set_synonym_provider][Rules::set_synonym_provider].
+pub trait SynonymProvider: Send + Sync {
+    /// Gets the synonyms of a word ordered from most to least suitable. Returns an empty vector for unknown words.
+    fn synonyms(&self, word: &str) -> Vec<String>;
+
+    /// Whether the suggestions of the given rule are expanded with synonyms.
+    /// By default, only rules with the category type `style` are expanded since their replacements are a matter of choice.
+    fn applies_to(&self, rule: &Rule) -> bool {
+        rule.category_type() == Some("style")
+    }
+}
+
+/// Appends the synonyms of each single-word replacement to the replacements of the suggestion, skipping duplicates.
+fn add_synonyms(suggestion: &mut Suggestion, provider: &dyn SynonymProvider) {
+    let synonyms: Vec<_> = suggestion
+        .replacements
+        .iter()
+        .filter(|x| !x.is_empty() && !x.contains(char::is_whitespace))
+        .flat_map(|x| provider.synonyms(x))
+        .collect();
+
+    for synonym in synonyms {
+        if !suggestion.replacements.contains(&synonym) {
+            suggestion.replacements.push(synonym);
+        }
+    }
 }
 
 impl Rules {
     /// Creates a new rules set from a file.
+    ///
+    /// # Memory
+    /// The file is streamed through a buffered reader, so it is never held in memory as a whole. However, all rules are
+    /// deserialized eagerly and all their regular expressions are compiled during loading, so memory usage and load time
+    /// scale with the total number of rules, not with the number of rules used. Loading lazily or memory-mapping the binary is not
+    /// supported since the rules are stored as one bincode sequence without an index. If only some rules are needed, build
+    /// a smaller binary instead (e. g. with [RulesOptions::with_ids]) or drop unused rules with [retain][Rules::retain] after loading to free their memory.
     pub fn new<P: AsRef<Path>>(p: P) -> bincode::Result<Self> {
         let reader = BufReader::new(File::open(p).unwrap());
         bincode::deserialize_from(reader)
@@ -62,21 +173,314 @@ impl Rules {
         self.rules.iter().find(|x| x.id() == id)
     }
 
+    /// Adds a rule to this set. The rule comes last in [rules][Rules::rules]. With the default [OverlapStrategy::FirstWins],
+    /// a suggestion of the pushed rule wins over a suggestion with the same start from an existing rule
+    /// with the same [priority][Rule::priority], use [set_priority][Rule::set_priority] to change that.
+    /// Data derived from the rules such as [trigger_words][Rules::trigger_words] must be recomputed after mutation.
+    pub fn push(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    /// Removes the rule with the given ID from this set and returns it if it exists.
+    /// Data derived from the rules such as [trigger_words][Rules::trigger_words] must be recomputed after mutation.
+    pub fn remove_by_id(&mut self, id: &str) -> Option<Rule> {
+        let index = self.rules.iter().position(|x| x.id() == id)?;
+        Some(self.rules.remove(index))
+    }
+
+    /// Keeps only the rules for which `f` returns `true` and frees the memory of the others, preserving the order of the kept rules.
+    /// Data derived from the rules such as [trigger_words][Rules::trigger_words] must be recomputed after mutation.
+    pub fn retain<F: FnMut(&Rule) -> bool>(&mut self, f: F) {
+        self.rules.retain(f);
+        self.rules.shrink_to_fit();
+    }
+
+    /// Combines this set with another set so both can be checked with a single call e. g. to [suggest][Rules::suggest],
+    /// which also resolves overlaps between suggestions from rules of different sets.
+    /// The rules of `other` come after the rules of this set, so with the default [OverlapStrategy::FirstWins], their suggestions
+    /// win over suggestions with the same start from rules of this set with the same [priority][Rule::priority] (as for [push][Rules::push]).
+    ///
+    /// If a rule ID occurs in both sets, a warning is logged and both rules are kept. [rule][Rules::rule] then returns the rule from this set.
+    pub fn merge(mut self, other: Rules) -> Rules {
+        let ids: HashSet<&str> = self.rules.iter().map(|x| x.id()).collect();
+
+        for rule in other.rules.iter().filter(|x| ids.contains(x.id())) {
+            warn!("Rule ID {} occurs in both merged rule sets.", rule.id());
+        }
+
+        self.rules.extend(other.rules);
+        self.post_processors.extend(other.post_processors);
+        self.synonym_provider = self.synonym_provider.or(other.synonym_provider);
+        self
+    }
+
+    /// Sets a thesaurus used to append synonyms of the replacements to the suggestions of some rules (see [SynonymProvider::applies_to]).
+    /// Synonyms are added before [post-processors][Rules::add_post_processor] run. No provider is set by default,
+    /// so suggestions are unchanged unless one is set. The provider is not serialized, so it must be set again after loading.
+    pub fn set_synonym_provider(&mut self, provider: Option<Arc<dyn SynonymProvider>>) {
+        self.synonym_provider = provider;
+    }
+
+    /// Registers a function which is called on every suggestion after it is generated by a rule and before overlaps
+    /// between suggestions are resolved e. g. to apply a house style to the replacements. Post-processors run
+    /// in the order they were added. They are not serialized, so they must be registered again after loading.
+    pub fn add_post_processor<F>(&mut self, f: F)
+    where
+        F: Fn(&mut Suggestion) + Send + Sync + 'static,
+    {
+        self.post_processors.push(Arc::new(f));
+    }
+
+    /// Gets the literal token texts the rules in this set can react to.
+    /// Useful as a cheap pre-filter before calling [suggest][Rules::suggest].
+    ///
+    /// For each rule, the literals of one part of its pattern which must always match are collected,
+    /// so a rule which is represented can only trigger if the text contains at least one of its words.
+    /// The part does not have to be the first one, so rules matching multiple tokens are represented as well
+    /// (e. g. a rule for "could of" is represented by "could").
+    /// Comparison should be done case-insensitively since most matchers are not case sensitive.
+    ///
+    /// Rules which only match on regular expressions or part-of-speech tags, as well as rules
+    /// operating on the raw text instead of on tokens, are **not** represented in this set and must always run.
+    pub fn trigger_words(&self) -> HashSet<String> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.engine.required_literals())
+            .flatten()
+            .map(|x| x.to_string())
+            .collect()
+    }
+
     /// Compute the suggestions for the given tokens by checking all rules.
-    pub fn apply(&self, tokens: &[Token], tokenizer: &Tokenizer) -> Vec<Suggestion> {
-        if tokens.is_empty() {
+    pub fn apply<T: AsTokens + ?Sized>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+    ) -> Vec<Suggestion> {
+        self.apply_with_filter(tokens, tokenizer, |_| true)
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules which are turned on
+    /// and for which `predicate` returns `true`. Does not change whether rules are turned on.
+    pub fn apply_with_filter<T, F>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        predicate: F,
+    ) -> Vec<Suggestion>
+    where
+        T: AsTokens + ?Sized,
+        F: Fn(&Rule) -> bool + Sync,
+    {
+        if tokens.as_tokens().is_empty() {
             return Vec::new();
         }
 
-        let mut output: Vec<(usize, Suggestion)> = self
-            .rules
+        let output = self.apply_unresolved(tokens, tokenizer, predicate);
+        resolve_overlaps(
+            output,
+            tokens.as_tokens()[0].text.chars().count(),
+            OverlapStrategy::default(),
+            |i| self.rules[i].priority(),
+        )
+        .0
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules, ordered by relevance instead of position
+    /// e. g. to apply the most likely correction first. Overlaps are resolved like in [apply][Rules::apply].
+    ///
+    /// Suggestions are ranked by
+    /// 1. the [priority][Rule::priority] of their rule, highest first,
+    /// 2. the [confidence][Rule::confidence] of their rule, highest first. Suggestions from rules without a confidence come last,
+    /// 3. the length of their span, longest first,
+    /// 4. their start, earliest first.
+    pub fn apply_ranked<T: AsTokens + ?Sized>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+    ) -> Vec<Suggestion> {
+        if tokens.as_tokens().is_empty() {
+            return Vec::new();
+        }
+
+        let output = self.apply_unresolved(tokens, tokenizer, |_| true);
+        let kept = resolve_overlaps_indexed(
+            output,
+            tokens.as_tokens()[0].text.chars().count(),
+            OverlapStrategy::default(),
+            |i| self.rules[i].priority(),
+        )
+        .0;
+
+        rank(kept, |i| self.rules[i].priority())
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules.
+    /// Overlapping suggestions are resolved according to `strategy`.
+    pub fn apply_with_strategy<T: AsTokens + ?Sized>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        strategy: OverlapStrategy,
+    ) -> Vec<Suggestion> {
+        if tokens.as_tokens().is_empty() {
+            return Vec::new();
+        }
+
+        let output = self.apply_unresolved(tokens, tokenizer, |_| true);
+        let n_chars = tokens.as_tokens()[0].text.chars().count();
+        resolve_overlaps(output, n_chars, strategy, |i| self.rules[i].priority()).0
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules until `deadline` has passed,
+    /// e. g. to bound the latency of a server. The clock is checked before each rule, rules which have not
+    /// started when the deadline passes are skipped. A rule which already started is not interrupted.
+    ///
+    /// Returns the suggestions and whether all rules were checked. If not, the suggestions are incomplete:
+    /// they only contain suggestions of the rules checked in time, which are not necessarily the rules with the highest priority.
+    pub fn apply_with_deadline<T: AsTokens + ?Sized>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        deadline: Instant,
+    ) -> (Vec<Suggestion>, bool) {
+        if tokens.as_tokens().is_empty() {
+            return (Vec::new(), true);
+        }
+
+        let timed_out = AtomicBool::new(false);
+        let output = self.apply_unresolved(tokens, tokenizer, |_| {
+            if Instant::now() < deadline {
+                true
+            } else {
+                timed_out.store(true, Ordering::Relaxed);
+                false
+            }
+        });
+
+        let suggestions = resolve_overlaps(
+            output,
+            tokens.as_tokens()[0].text.chars().count(),
+            OverlapStrategy::default(),
+            |i| self.rules[i].priority(),
+        )
+        .0;
+
+        (suggestions, !timed_out.load(Ordering::Relaxed))
+    }
+
+    /// Gets the IDs of all rules which are on and match the given tokens, e. g. for telemetry.
+    /// Only runs the pattern matching, so this is cheaper than [apply][Rules::apply] but a rule can be
+    /// contained even if it would not produce a suggestion (e. g. because no replacement can be generated).
+    pub fn matched_rule_ids(&self, tokens: &[Token]) -> HashSet<String> {
+        if tokens.is_empty() {
+            return HashSet::new();
+        }
+
+        self.rules
             .maybe_par_iter()
+            .filter(|x| x.on() && x.is_match(tokens))
+            .map(|x| x.id().to_string())
+            .collect()
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules with a confidence of at least `min_confidence`.
+    /// Rules without a confidence are treated as having a confidence of 1.0. See [confidence][Rule::confidence].
+    pub fn apply_above<T: AsTokens + ?Sized>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        min_confidence: f32,
+    ) -> Vec<Suggestion> {
+        self.apply_with_filter(tokens, tokenizer, |rule| {
+            rule.confidence().unwrap_or(1.0) >= min_confidence
+        })
+    }
+
+    /// Compute the suggestions for the given tokens by checking all rules. Rules are checked in parallel
+    /// on the threads of `pool` instead of the global Rayon thread pool, e. g. to bound the resources used by nlprule.
+    pub fn apply_in_pool<T: AsTokens + Sync + ?Sized>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        pool: &rayon::ThreadPool,
+    ) -> Vec<Suggestion> {
+        pool.install(|| self.apply(tokens, tokenizer))
+    }
+
+    /// Cheap pre-pass to check whether none of the `rules` can match the tokens because each of them requires
+    /// one of its [trigger words][Rules::trigger_words] and none of these occur in the tokens.
+    /// Always `false` if one of the rules has no trigger words.
+    fn none_can_trigger(rules: &[(usize, &Rule)], tokens: &[Token]) -> bool {
+        let mut texts: Option<BloomFilter> = None;
+
+        rules
+            .iter()
+            .all(|(_, rule)| match rule.engine.required_literals() {
+                Some(literals) => {
+                    let texts = texts.get_or_insert_with(|| {
+                        BloomFilter::new(tokens.iter().map(|x| x.word.text.as_ref()))
+                    });
+
+                    literals.into_iter().all(|x| !texts.might_contain(x))
+                }
+                None => false,
+            })
+    }
+
+    /// Computes the suggestions of all rules which are on and for which `predicate` returns `true`
+    /// together with the index of the rule. Overlapping suggestions are not resolved yet.
+    fn apply_unresolved<T, F>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        predicate: F,
+    ) -> Vec<(usize, Suggestion)>
+    where
+        T: AsTokens + ?Sized,
+        F: Fn(&Rule) -> bool + Sync,
+    {
+        self.apply_unresolved_cond(tokens, tokenizer, predicate, true)
+    }
+
+    /// Like [apply_unresolved][Rules::apply_unresolved], but the rules are only checked in parallel if `parallel` is set.
+    fn apply_unresolved_cond<T, F>(
+        &self,
+        tokens: &T,
+        tokenizer: &Tokenizer,
+        predicate: F,
+        parallel: bool,
+    ) -> Vec<(usize, Suggestion)>
+    where
+        T: AsTokens + ?Sized,
+        F: Fn(&Rule) -> bool + Sync,
+    {
+        let rules: Vec<_> = self
+            .rules
+            .iter()
             .enumerate()
-            .filter(|(_, x)| x.on())
-            .map(|(i, rule)| {
+            .filter(|(_, x)| x.on() && predicate(x))
+            .collect();
+
+        if Rules::none_can_trigger(&rules, tokens.as_tokens()) {
+            return Vec::new();
+        }
+
+        // only segment the text into sentences if needed, this is free for `Tokenized` but not for token slices
+        let sentence_starts = if rules.iter().any(|(_, x)| x.first_match_only()) {
+            Some(tokens.sentence_starts(tokenizer))
+        } else {
+            None
+        };
+        let starts = sentence_starts.as_deref().unwrap_or(&[]);
+        let token_slice = tokens.as_tokens();
+
+        let mut output: Vec<_> = rules
+            .maybe_par_iter_cond(parallel)
+            .map(|&(i, rule)| {
                 let mut output = Vec::new();
 
-                for suggestion in rule.apply(tokens, tokenizer) {
+                for suggestion in rule.apply(token_slice, starts, tokenizer) {
                     output.push((i, suggestion));
                 }
 
@@ -85,29 +489,415 @@ impl Rules {
             .flatten()
             .collect();
 
-        output.sort_by(|(ia, a), (ib, b)| a.start.cmp(&b.start).then_with(|| ib.cmp(ia)));
+        if !output.is_empty() {
+            let sentence_starts =
+                sentence_starts.unwrap_or_else(|| tokens.sentence_starts(tokenizer));
+            set_sentence_indices(output.iter_mut().map(|(_, x)| x), &sentence_starts);
+        }
 
-        let mut mask = vec![false; tokens[0].text.chars().count()];
+        self.post_process(&mut output);
 
         output
-            .into_iter()
-            .filter_map(|(_, suggestion)| {
-                if mask[suggestion.start..suggestion.end].iter().all(|x| !x) {
-                    mask[suggestion.start..suggestion.end]
-                        .iter_mut()
-                        .for_each(|x| *x = true);
-                    Some(suggestion)
-                } else {
-                    None
+    }
+
+    /// Adds synonyms and runs the post-processors on suggestions paired with the index of the rule they are from.
+    fn post_process(&self, output: &mut [(usize, Suggestion)]) {
+        if let Some(provider) = &self.synonym_provider {
+            for (i, suggestion) in output.iter_mut() {
+                if provider.applies_to(&self.rules[*i]) {
+                    add_synonyms(suggestion, provider.as_ref());
                 }
+            }
+        }
+
+        for post_processor in &self.post_processors {
+            for (_, suggestion) in output.iter_mut() {
+                post_processor(suggestion);
+            }
+        }
+    }
+
+    /// Compute the suggestions for a text by checking all rules and measure how long each rule takes.
+    /// Returns the suggestions and a map of rule ID to the time spent applying the rule.
+    /// Timing adds overhead, so this is only available with the `profile` feature.
+    #[cfg(feature = "profile")]
+    pub fn suggest_profiled(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+    ) -> (Vec<Suggestion>, HashMap<String, Duration>) {
+        let tokens = Tokenized::new(text, tokenizer);
+
+        if tokens.is_empty() {
+            return (Vec::new(), HashMap::new());
+        }
+        let sentence_starts = tokens.sentence_starts(tokenizer);
+
+        let results: Vec<_> = self
+            .rules
+            .maybe_par_iter()
+            .enumerate()
+            .filter(|(_, x)| x.on())
+            .map(|(i, rule)| {
+                let start = Instant::now();
+                let suggestions = rule.apply(&tokens, &sentence_starts, tokenizer);
+                let elapsed = start.elapsed();
+
+                (i, rule.id(), suggestions, elapsed)
             })
-            .collect()
+            .collect();
+
+        let mut timings: HashMap<String, Duration> = HashMap::new();
+        let mut output = Vec::new();
+
+        for (i, id, suggestions, elapsed) in results {
+            *timings.entry(id.to_string()).or_default() += elapsed;
+            output.extend(suggestions.into_iter().map(|x| (i, x)));
+        }
+
+        set_sentence_indices(output.iter_mut().map(|(_, x)| x), &sentence_starts);
+        self.post_process(&mut output);
+
+        (
+            resolve_overlaps(
+                output,
+                tokens[0].text.chars().count(),
+                OverlapStrategy::default(),
+                |i| self.rules[i].priority(),
+            )
+            .0,
+            timings,
+        )
     }
 
     /// Compute the suggestions for a text by checking all rules.
+    ///
+    /// The offsets of the suggestions are char indices into `text`. If `text` was derived from another text
+    /// (e. g. by Unicode normalization), the offsets do not refer to the original text and must be translated
+    /// with [remap_suggestions] before applying them to it.
+    ///
+    /// To check the same text multiple times (e. g. with different rules), tokenize it once with [Tokenized][crate::tokenizer::Tokenized]
+    /// and pass it to [apply][Rules::apply] instead.
     pub fn suggest(&self, text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
-        let tokens = tokenizer.disambiguate(tokenizer.tokenize(text));
-        self.apply(&finalize(tokens), tokenizer)
+        self.apply(&Tokenized::new(text, tokenizer), tokenizer)
+    }
+
+    /// Compute the suggestions for a text by checking all rules, ordered by relevance instead of position.
+    /// See [apply_ranked][Rules::apply_ranked] for the order.
+    pub fn suggest_ranked(&self, text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
+        self.apply_ranked(&Tokenized::new(text, tokenizer), tokenizer)
+    }
+
+    /// Compute the suggestions for a text by checking all rules.
+    /// Overlapping suggestions are resolved according to `strategy`.
+    pub fn suggest_with_strategy(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        strategy: OverlapStrategy,
+    ) -> Vec<Suggestion> {
+        self.apply_with_strategy(&Tokenized::new(text, tokenizer), tokenizer, strategy)
+    }
+
+    /// Compute the suggestions for a text by checking all rules with a confidence of at least `min_confidence`.
+    /// See [apply_above][Rules::apply_above].
+    pub fn suggest_above(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        min_confidence: f32,
+    ) -> Vec<Suggestion> {
+        self.apply_above(&Tokenized::new(text, tokenizer), tokenizer, min_confidence)
+    }
+
+    /// Compute the suggestions for a text by checking all rules. Disambiguation and rule checking run
+    /// on the threads of `pool` instead of the global Rayon thread pool. See [apply_in_pool][Rules::apply_in_pool].
+    pub fn suggest_in_pool(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        pool: &rayon::ThreadPool,
+    ) -> Vec<Suggestion> {
+        pool.install(|| self.suggest(text, tokenizer))
+    }
+
+    /// Compute the suggestions for a text separately for each sentence of the text.
+    /// Returns each sentence (without trailing whitespace) together with its suggestions.
+    /// The offsets of the suggestions are relative to the start of the sentence.
+    pub fn suggest_by_sentence(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+    ) -> Vec<(String, Vec<Suggestion>)> {
+        self.suggest_by_sentence_with_context(text, tokenizer, 0)
+    }
+
+    /// Like [suggest_by_sentence][Rules::suggest_by_sentence], but each sentence is checked together with up to
+    /// `n_prev_sentences` preceding sentences as read-only context, so rules can match across sentence boundaries
+    /// (e. g. to find the antecedent of "it"). Suggestions are still only returned if they start within the current sentence.
+    pub fn suggest_by_sentence_with_context(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        n_prev_sentences: usize,
+    ) -> Vec<(String, Vec<Suggestion>)> {
+        let spans = tokenizer.sentence_spans(text);
+
+        spans
+            .iter()
+            .enumerate()
+            .map(|(i, (start, end))| {
+                let sentence = text[*start..*end].trim_end();
+
+                let context_start = spans[i.saturating_sub(n_prev_sentences)].0;
+                let n_context_chars = text[context_start..*start].chars().count();
+
+                let context = &text[context_start..*start + sentence.len()];
+                let tokens = Tokenized::new(context, tokenizer);
+
+                // suggestions in the context are dropped before resolving overlaps so they can not suppress
+                // suggestions in the current sentence
+                let output = if tokens.is_empty() {
+                    Vec::new()
+                } else {
+                    self.apply_unresolved(&tokens, tokenizer, |_| true)
+                        .into_iter()
+                        .filter(|(_, x)| x.start >= n_context_chars)
+                        .collect()
+                };
+
+                let suggestions = resolve_overlaps(
+                    output,
+                    context.chars().count(),
+                    OverlapStrategy::default(),
+                    |i| self.rules[i].priority(),
+                )
+                .0
+                .into_iter()
+                .map(|mut x| {
+                    x.start -= n_context_chars;
+                    x.end -= n_context_chars;
+                    x.sentence_index = i;
+                    x
+                })
+                .collect();
+
+                (sentence.to_string(), suggestions)
+            })
+            .collect()
+    }
+
+    /// Compute the suggestions covering the character at `char_offset` e. g. for a hover in an editor. Only the sentence
+    /// containing the offset is checked, so this is much faster than [suggest][Rules::suggest] for long texts. Offsets of
+    /// the suggestions are relative to the start of `text`. An insertion (i. e. a suggestion with an empty span) covers the offset it is at.
+    /// Rules matching across sentence boundaries can not produce suggestions here.
+    pub fn suggest_at(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        char_offset: usize,
+    ) -> Vec<Suggestion> {
+        let byte_offset = text
+            .char_indices()
+            .nth(char_offset)
+            .map_or(text.len(), |(i, _)| i);
+
+        let spans = tokenizer.sentence_spans(text);
+        let (index, (start, end)) = match spans
+            .iter()
+            .enumerate()
+            .find(|(_, (start, end))| byte_offset >= *start && byte_offset < *end)
+            .or_else(|| spans.iter().enumerate().next_back())
+        {
+            Some((index, span)) => (index, *span),
+            None => return Vec::new(),
+        };
+
+        let n_prev_chars = text[..start].chars().count();
+
+        self.suggest(&text[start..end], tokenizer)
+            .into_iter()
+            .map(|mut x| {
+                x.start += n_prev_chars;
+                x.end += n_prev_chars;
+                x.sentence_index = index;
+                x
+            })
+            .filter(|x| {
+                (x.start <= char_offset && char_offset < x.end)
+                    || (x.start == x.end && x.start == char_offset)
+            })
+            .collect()
+    }
+
+    /// Compute the suggestions for a text by checking all rules until `deadline` has passed.
+    /// See [apply_with_deadline][Rules::apply_with_deadline], the time to tokenize the text counts towards the deadline.
+    pub fn suggest_with_deadline(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        deadline: Instant,
+    ) -> (Vec<Suggestion>, bool) {
+        self.apply_with_deadline(&Tokenized::new(text, tokenizer), tokenizer, deadline)
+    }
+
+    /// Compute the suggestions for a text by checking all rules which either apply to the given language
+    /// (e. g. "de") or are not restricted to a language. See [language][Rule::language].
+    pub fn suggest_for_language(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        language: &str,
+    ) -> Vec<Suggestion> {
+        self.suggest_with_filter(text, tokenizer, |rule| {
+            rule.language().is_none_or(|x| x == language)
+        })
+    }
+
+    /// Compute the suggestions for a text by checking all rules and additionally return the suggestions
+    /// which were suppressed because they overlap with a suggestion of a rule with higher priority.
+    /// The [source][Suggestion::source] of a suppressed suggestion is the ID of the rule it is from.
+    /// Useful to find out whether a rule did not match or whether its suggestion was lost to an overlap.
+    pub fn suggest_with_stats(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+    ) -> (Vec<Suggestion>, Vec<Suggestion>) {
+        let tokens = Tokenized::new(text, tokenizer);
+
+        if tokens.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let output = self.apply_unresolved(&tokens, tokenizer, |_| true);
+        resolve_overlaps(
+            output,
+            tokens[0].text.chars().count(),
+            OverlapStrategy::default(),
+            |i| self.rules[i].priority(),
+        )
+    }
+
+    /// Compute the suggestions for a text by checking all rules and keep only the [best][Suggestion::best]
+    /// replacement of each suggestion, e. g. for automatic correction.
+    pub fn suggest_autocorrect(&self, text: &str, tokenizer: &Tokenizer) -> Vec<Suggestion> {
+        self.suggest(text, tokenizer)
+            .into_iter()
+            .map(|mut x| {
+                x.replacements.truncate(1);
+                x
+            })
+            .collect()
+    }
+
+    /// Compute the suggestions for a text and yield them as [Diagnostic]s with byte offsets and a severity,
+    /// e. g. to publish diagnostics from a language server. The suggestions are computed upfront since overlaps
+    /// must be resolved, conversion to diagnostics happens lazily.
+    pub fn diagnostics<'a>(
+        &'a self,
+        text: &'a str,
+        tokenizer: &Tokenizer,
+    ) -> impl Iterator<Item = Diagnostic> + 'a {
+        let byte_indices: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+
+        self.suggest(text, tokenizer)
+            .into_iter()
+            .map(move |suggestion| Diagnostic {
+                byte_span: (byte_indices[suggestion.start], byte_indices[suggestion.end]),
+                severity: Severity::from_category_type(
+                    self.rule(&suggestion.source)
+                        .and_then(|x| x.category_type()),
+                ),
+                rule_id: suggestion.source,
+                message: suggestion.message,
+                replacements: suggestion.replacements,
+            })
+    }
+
+    /// Annotates each token of the text with the IDs of the rules which are on and match at the token,
+    /// e. g. to create datasets. A rule matches at a token if the token is within the part of the match
+    /// the rule would replace. Overlapping matches are not resolved, so a token can have multiple rules.
+    pub fn annotate(&self, text: &str, tokenizer: &Tokenizer) -> Vec<TokenAnnotation> {
+        let tokens = finalize(tokenizer.disambiguate(tokenizer.tokenize(text)));
+
+        let matches: Vec<(&str, (usize, usize))> = self
+            .rules
+            .maybe_par_iter()
+            .filter(|x| x.on())
+            .map(|rule| {
+                rule.matches(&tokens)
+                    .into_iter()
+                    .filter_map(|graph| {
+                        let start = graph.by_id(rule.start)?.char_span.0;
+                        let end = graph.by_id(rule.end - 1)?.char_span.1;
+                        Some((rule.id(), (start, end)))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .flatten()
+            .collect();
+
+        tokens
+            .iter()
+            .filter(|x| x.char_span.0 < x.char_span.1)
+            .map(|token| {
+                let mut rule_ids: Vec<String> = Vec::new();
+
+                for (id, (start, end)) in &matches {
+                    if token.char_span.0 >= *start
+                        && token.char_span.1 <= *end
+                        && !rule_ids.iter().any(|x| x == id)
+                    {
+                        rule_ids.push(id.to_string());
+                    }
+                }
+
+                TokenAnnotation {
+                    text: token.word.text.as_ref().to_string(),
+                    pos: token
+                        .word
+                        .tags
+                        .iter()
+                        .map(|x| x.pos.as_ref())
+                        .filter(|x| !x.is_empty())
+                        .map(|x| x.to_string())
+                        .collect(),
+                    char_span: token.char_span,
+                    rule_ids,
+                }
+            })
+            .collect()
+    }
+
+    /// Compute the suggestions for a text by checking all rules and serialize them to a JSON array
+    /// of [SuggestionView]s. Only available with the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn suggest_json(&self, text: &str, tokenizer: &Tokenizer) -> String {
+        let views: Vec<SuggestionView> = self
+            .suggest(text, tokenizer)
+            .iter()
+            .map(SuggestionView::from)
+            .collect();
+
+        serde_json::to_string(&views).expect("suggestions can always be serialized")
+    }
+
+    /// Compute the suggestions for a text by checking all rules for which `predicate` returns `true`.
+    /// See [apply_with_filter][Rules::apply_with_filter].
+    pub fn suggest_with_filter<F>(
+        &self,
+        text: &str,
+        tokenizer: &Tokenizer,
+        predicate: F,
+    ) -> Vec<Suggestion>
+    where
+        F: Fn(&Rule) -> bool + Sync,
+    {
+        self.apply_with_filter(&Tokenized::new(text, tokenizer), tokenizer, predicate)
     }
 
     /// Correct a text by first tokenizing, then finding all suggestions and choosing the first replacement of each suggestion.
@@ -115,23 +905,1352 @@ impl Rules {
         let suggestions = self.suggest(text, tokenizer);
         apply_suggestions(text, &suggestions)
     }
+
+    /// Corrects many independent texts, like calling [correct][Rules::correct] on each text. The texts are processed in parallel
+    /// while the rules (and disambiguation rules) for each text are checked sequentially, which distributes the work better
+    /// than [correct][Rules::correct] if there are many short texts. The output is in the order of `texts`.
+    /// Parallelism can be disabled with the `NLPRULE_PARALLELISM` environment variable.
+    pub fn correct_batch(&self, texts: &[String], tokenizer: &Tokenizer) -> Vec<String> {
+        texts
+            .maybe_par_iter()
+            .map(|text| {
+                let tokens = finalize(tokenizer.disambiguate_up_to_id(
+                    tokenizer.tokenize(text),
+                    None,
+                    false,
+                ));
+
+                if tokens.is_empty() {
+                    return text.clone();
+                }
+
+                let output = self.apply_unresolved_cond(&tokens, tokenizer, |_| true, false);
+                let suggestions = resolve_overlaps(
+                    output,
+                    text.chars().count(),
+                    OverlapStrategy::default(),
+                    |i| self.rules[i].priority(),
+                )
+                .0;
+
+                apply_suggestions(text, &suggestions)
+            })
+            .collect()
+    }
+
+    /// Like [correct][Rules::correct], but if `preserve_case` is set, transfers the capitalization of the replaced text
+    /// onto each replacement, e. g. so that fixing "Teh" at the start of a sentence results in "The" instead of "the".
+    /// See [apply_suggestions_preserving_case].
+    pub fn correct_with(&self, text: &str, tokenizer: &Tokenizer, preserve_case: bool) -> String {
+        let suggestions = self.suggest(text, tokenizer);
+
+        if preserve_case {
+            apply_suggestions_preserving_case(text, &suggestions)
+        } else {
+            apply_suggestions(text, &suggestions)
+        }
+    }
+
+    /// Updates suggestions after one of them has been applied with [apply_one], e. g. during interactive correction.
+    /// Only the sentence containing the edit is checked again, the other suggestions are kept and their offsets are shifted
+    /// to be valid in the edited text.
+    ///
+    /// # Arguments
+    /// * `edited`: The text after applying `applied`.
+    /// * `suggestions`: The suggestions computed for the text before the edit.
+    /// * `applied`: The suggestion which was applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use nlprule::{Tokenizer, Rules, rules::apply_one};
+    ///
+    /// let tokenizer = Tokenizer::new("path/to/en_tokenizer.bin")?;
+    /// let rules = Rules::new("path/to/en_rules.bin")?;
+    ///
+    /// let mut text = "She was not been here since Monday. Its a nice day.".to_string();
+    /// let mut suggestions = rules.suggest(&text, &tokenizer);
+    ///
+    /// while let Some(suggestion) = suggestions.first().cloned() {
+    ///     text = apply_one(&text, &suggestion);
+    ///     suggestions = rules.update_suggestions(&text, &suggestions, &suggestion, &tokenizer);
+    /// }
+    /// # Ok::<(), bincode::Error>(())
+    /// ```
+    pub fn update_suggestions(
+        &self,
+        edited: &str,
+        suggestions: &[Suggestion],
+        applied: &Suggestion,
+        tokenizer: &Tokenizer,
+    ) -> Vec<Suggestion> {
+        // a suggestion without replacements can not be applied, so the text is unchanged
+        let n_inserted = applied
+            .replacements
+            .first()
+            .map_or(applied.end - applied.start, |x| x.chars().count());
+        let delta = n_inserted as isize - (applied.end - applied.start) as isize;
+
+        let byte_index = |char_idx| {
+            edited
+                .char_indices()
+                .nth(char_idx)
+                .map_or(edited.len(), |(i, _)| i)
+        };
+        let (byte_start, byte_end) = sentence_range(
+            &tokenizer.sentence_spans(edited),
+            edited,
+            byte_index(applied.start),
+            byte_index(applied.start + n_inserted),
+        );
+        let sentence_start = edited[..byte_start].chars().count();
+        let sentence_end = sentence_start + edited[byte_start..byte_end].chars().count();
+
+        let mut output: Vec<_> = shift_suggestions(suggestions, applied, delta)
+            .into_iter()
+            .filter(|x| x.end <= sentence_start || x.start >= sentence_end)
+            .collect();
+
+        output.extend(
+            self.suggest(&edited[byte_start..byte_end], tokenizer)
+                .into_iter()
+                .map(|mut x| {
+                    x.start += sentence_start;
+                    x.end += sentence_start;
+                    x
+                }),
+        );
+
+        set_sentence_indices(output.iter_mut(), &tokenizer.sentence_starts(edited));
+        output.sort_by_key(|x| x.start);
+        output
+    }
+}
+
+/// Sets the [sentence index][Suggestion::sentence_index] of each suggestion given the char index of the start of each sentence.
+fn set_sentence_indices<'a, I>(suggestions: I, starts: &[usize])
+where
+    I: Iterator<Item = &'a mut Suggestion>,
+{
+    for suggestion in suggestions {
+        suggestion.sentence_index = starts
+            .partition_point(|x| *x <= suggestion.start)
+            .saturating_sub(1);
+    }
+}
+
+/// Removes overlapping suggestions according to `strategy`. The suggestions are paired with the index of
+/// the rule they are from, `priority` gets the [priority][Rule::priority] of the rule at an index.
+fn resolve_overlaps<F>(
+    output: Vec<(usize, Suggestion)>,
+    n_chars: usize,
+    strategy: OverlapStrategy,
+    priority: F,
+) -> (Vec<Suggestion>, Vec<Suggestion>)
+where
+    F: Fn(usize) -> i32,
+{
+    let (kept, suppressed) = resolve_overlaps_indexed(output, n_chars, strategy, priority);
+    let strip = |x: Vec<(usize, Suggestion)>| x.into_iter().map(|(_, x)| x).collect();
+
+    (strip(kept), strip(suppressed))
+}
+
+/// Like [resolve_overlaps], but keeps the rule index paired with each suggestion.
+#[allow(clippy::type_complexity)]
+fn resolve_overlaps_indexed<F>(
+    mut output: Vec<(usize, Suggestion)>,
+    n_chars: usize,
+    strategy: OverlapStrategy,
+    priority: F,
+) -> (Vec<(usize, Suggestion)>, Vec<(usize, Suggestion)>)
+where
+    F: Fn(usize) -> i32,
+{
+    match strategy {
+        OverlapStrategy::FirstWins => output.sort_by(|(ia, a), (ib, b)| {
+            priority(*ib)
+                .cmp(&priority(*ia))
+                .then_with(|| a.start.cmp(&b.start))
+                .then_with(|| ib.cmp(ia))
+        }),
+        OverlapStrategy::LongestWins => output.sort_by(|(ia, a), (ib, b)| {
+            (b.end - b.start)
+                .cmp(&(a.end - a.start))
+                .then_with(|| priority(*ib).cmp(&priority(*ia)))
+                .then_with(|| a.start.cmp(&b.start))
+        }),
+        OverlapStrategy::PriorityWins => output.sort_by(|(ia, a), (ib, b)| {
+            priority(*ib)
+                .cmp(&priority(*ia))
+                .then_with(|| (b.end - b.start).cmp(&(a.end - a.start)))
+                .then_with(|| ia.cmp(ib))
+                .then_with(|| a.start.cmp(&b.start))
+        }),
+    }
+
+    let mut mask = vec![false; n_chars];
+    let mut insertions = Vec::new();
+    let mut kept = Vec::new();
+    let mut suppressed = Vec::new();
+
+    for (i, suggestion) in output {
+        let is_free = if suggestion.start == suggestion.end {
+            // insertions do not occupy any chars, but must not be inside another suggestion
+            let idx = suggestion.start;
+            let is_inside = idx > 0 && idx < mask.len() && mask[idx - 1] && mask[idx];
+
+            if !is_inside {
+                insertions.push(idx);
+            }
+            !is_inside
+        } else if mask[suggestion.start..suggestion.end].iter().all(|x| !x)
+            && !insertions
+                .iter()
+                .any(|idx| *idx > suggestion.start && *idx < suggestion.end)
+        {
+            mask[suggestion.start..suggestion.end]
+                .iter_mut()
+                .for_each(|x| *x = true);
+            true
+        } else {
+            false
+        };
+
+        if is_free {
+            kept.push((i, suggestion));
+        } else {
+            suppressed.push((i, suggestion));
+        }
+    }
+
+    kept.sort_by_key(|(_, x)| x.start);
+    suppressed.sort_by_key(|(_, x)| x.start);
+
+    (kept, suppressed)
+}
+
+/// Sorts suggestions paired with the index of the rule they are from by relevance, see [apply_ranked][Rules::apply_ranked].
+fn rank<F>(mut output: Vec<(usize, Suggestion)>, priority: F) -> Vec<Suggestion>
+where
+    F: Fn(usize) -> i32,
+{
+    output.sort_by(|(ia, a), (ib, b)| {
+        priority(*ib)
+            .cmp(&priority(*ia))
+            // `None` is less than any `Some`, so suggestions without confidence come last
+            .then_with(|| {
+                b.confidence
+                    .partial_cmp(&a.confidence)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .then_with(|| (b.end - b.start).cmp(&(a.end - a.start)))
+            .then_with(|| a.start.cmp(&b.start))
+    });
+
+    output.into_iter().map(|(_, x)| x).collect()
+}
+
+/// Removes `applied` (and suggestions overlapping with it) from `suggestions` and
+/// shifts suggestions after it by `delta` chars.
+fn shift_suggestions(
+    suggestions: &[Suggestion],
+    applied: &Suggestion,
+    delta: isize,
+) -> Vec<Suggestion> {
+    suggestions
+        .iter()
+        .filter(|x| x.end <= applied.start || x.start >= applied.end)
+        .filter(|x| {
+            !(x.start == applied.start && x.end == applied.end && x.source == applied.source)
+        })
+        .cloned()
+        .map(|mut x| {
+            if x.start >= applied.end {
+                x.start = (x.start as isize + delta) as usize;
+                x.end = (x.end as isize + delta) as usize;
+            }
+            x
+        })
+        .collect()
+}
+
+/// Correct a text by applying one suggestion to it, choosing the first replacement.
+/// Offsets of other suggestions for the same text are invalid afterwards, see [Rules::update_suggestions].
+pub fn apply_one(text: &str, suggestion: &Suggestion) -> String {
+    apply_suggestions(text, std::slice::from_ref(suggestion))
 }
 
 /// Correct a text by applying suggestions to it.
 /// In the case of multiple possible replacements, always chooses the first one.
+///
+/// Suggestions must be sorted by start index and must not overlap. A suggestion with `start == end`
+/// is an insertion, a suggestion with an empty replacement is a deletion. Suggestions without replacements are skipped.
 pub fn apply_suggestions(text: &str, suggestions: &[Suggestion]) -> String {
+    apply_suggestions_inner(text, suggestions, false)
+}
+
+/// Like [apply_suggestions], but also returns the indices (into `suggestions`) of the suggestions which were applied,
+/// e. g. for audit logs. Suggestions do not need to be sorted. A suggestion is skipped if it is not
+/// [valid][Suggestion::is_valid_for] for the text or if it overlaps with a suggestion starting before it.
+pub fn correct_verbose(text: &str, suggestions: &[Suggestion]) -> (String, Vec<usize>) {
+    let mut applied = applicable_suggestions(text, suggestions);
+
+    let to_apply: Vec<_> = applied.iter().map(|i| suggestions[*i].clone()).collect();
+    applied.sort_unstable();
+
+    (apply_suggestions(text, &to_apply), applied)
+}
+
+/// Gets the indices of the suggestions which can be applied to `text`, ordered by start index.
+fn applicable_suggestions(text: &str, suggestions: &[Suggestion]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..suggestions.len()).collect();
+    order.sort_by_key(|i| suggestions[*i].start);
+
+    let mut applied = Vec::new();
+    let mut prev_end = 0;
+
+    for i in order {
+        let suggestion = &suggestions[i];
+
+        if suggestion.is_valid_for(text) && suggestion.start >= prev_end {
+            prev_end = suggestion.end;
+            applied.push(i);
+        }
+    }
+
+    applied
+}
+
+/// Computes the diff between `text` and the text corrected with the first replacement of each suggestion,
+/// e. g. to render the changes for review. Since it is derived from the suggestions, the diff is never ambiguous.
+/// Suggestions are selected as in [correct_verbose]. Adjacent operations of the same kind are not merged.
+pub fn suggestions_to_diff(text: &str, suggestions: &[Suggestion]) -> Vec<DiffOp> {
+    let chars: Vec<_> = text.chars().collect();
+    let mut ops = Vec::new();
+    let mut prev_end = 0;
+
+    for i in applicable_suggestions(text, suggestions) {
+        let suggestion = &suggestions[i];
+
+        if suggestion.start > prev_end {
+            ops.push(DiffOp::Equal(
+                chars[prev_end..suggestion.start].iter().collect(),
+            ));
+        }
+        if suggestion.end > suggestion.start {
+            ops.push(DiffOp::Delete(
+                chars[suggestion.start..suggestion.end].iter().collect(),
+            ));
+        }
+        if let Some(best) = suggestion.best().filter(|x| !x.is_empty()) {
+            ops.push(DiffOp::Insert(best.to_string()));
+        }
+
+        prev_end = suggestion.end;
+    }
+
+    if prev_end < chars.len() {
+        ops.push(DiffOp::Equal(chars[prev_end..].iter().collect()));
+    }
+
+    ops
+}
+
+/// Like [apply_suggestions], but transfers the capitalization of the replaced text onto the replacement:
+/// if the replaced text is all uppercase, so is the replacement, if it starts with an uppercase letter,
+/// so does the replacement. Lowercase text does not change the replacement, since rules capitalize
+/// replacements on purpose e. g. for proper nouns. Suggestions which only change the case of the text are kept as-is.
+pub fn apply_suggestions_preserving_case(text: &str, suggestions: &[Suggestion]) -> String {
+    apply_suggestions_inner(text, suggestions, true)
+}
+
+fn apply_suggestions_inner(text: &str, suggestions: &[Suggestion], preserve_case: bool) -> String {
     let mut offset: isize = 0;
     let mut chars: Vec<_> = text.chars().collect();
 
     for suggestion in suggestions {
-        let replacement: Vec<_> = suggestion.replacements[0].chars().collect();
-        chars.splice(
-            (suggestion.start as isize + offset) as usize
-                ..(suggestion.end as isize + offset) as usize,
-            replacement.iter().cloned(),
-        );
-        offset = offset + replacement.len() as isize - (suggestion.end - suggestion.start) as isize;
+        let best = match suggestion.best() {
+            Some(best) => best,
+            None => continue,
+        };
+        let start = (suggestion.start as isize + offset) as usize;
+        let end = (suggestion.end as isize + offset) as usize;
+
+        let replacement: Vec<_> = if preserve_case {
+            let original: String = chars[start..end].iter().collect();
+            utils::transfer_case(&original, best).chars().collect()
+        } else {
+            best.chars().collect()
+        };
+
+        if start == end {
+            // zero-width span: nothing is removed, the replacement is inserted before `start`
+            chars.splice(start..start, replacement.iter().cloned());
+        } else if replacement.is_empty() {
+            chars.drain(start..end);
+        } else {
+            chars.splice(start..end, replacement.iter().cloned());
+        }
+
+        offset += replacement.len() as isize - (suggestion.end - suggestion.start) as isize;
     }
 
     chars.into_iter().collect()
 }
+
+/// An error indicating that suggestions can not be applied to a text, see [validate_suggestions].
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    #[error("suggestion {index} has an invalid span or no replacements")]
+    InvalidSpan { index: usize },
+    #[error("suggestion {index} overlaps with or starts before the previous suggestion")]
+    Overlap { index: usize },
+}
+
+/// Checks that suggestions can safely be passed to [apply_suggestions] for `text`: each must be
+/// [valid][Suggestion::is_valid_for] for the text and they must be sorted by start index without overlapping.
+pub fn validate_suggestions(text: &str, suggestions: &[Suggestion]) -> Result<(), ValidationError> {
+    validate_spans(text, suggestions, true)
+}
+
+/// Like [validate_suggestions], but suggestions without replacements are only rejected if `require_replacements` is set.
+fn validate_spans(
+    text: &str,
+    suggestions: &[Suggestion],
+    require_replacements: bool,
+) -> Result<(), ValidationError> {
+    let n_chars = text.chars().count();
+    let mut prev_end = 0;
+
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        if suggestion.start > suggestion.end
+            || suggestion.end > n_chars
+            || (require_replacements && suggestion.replacements.is_empty())
+        {
+            return Err(ValidationError::InvalidSpan { index });
+        }
+
+        if suggestion.start < prev_end {
+            return Err(ValidationError::Overlap { index });
+        }
+
+        prev_end = suggestion.end;
+    }
+
+    Ok(())
+}
+
+/// Like [apply_suggestions], but writes the corrected text into `text` instead of returning a new string.
+///
+/// Suggestions must be sorted by start index and must not overlap. Suggestions without replacements are skipped.
+///
+/// # Errors
+///
+/// Returns an error and leaves `text` unchanged if a suggestion spans beyond the text, the suggestions are not sorted
+/// or they overlap, see [validate_suggestions].
+pub fn correct_in_place(
+    text: &mut String,
+    suggestions: &[Suggestion],
+) -> Result<(), ValidationError> {
+    validate_spans(text, suggestions, false)?;
+
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+
+    // char index -> byte index for all suggestion boundaries, computed before the text is changed
+    let mut boundaries: Vec<usize> = suggestions
+        .iter()
+        .flat_map(|x| std::iter::once(x.start).chain(std::iter::once(x.end)))
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut byte_indices = DefaultHashMap::default();
+    let mut char_indices = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .enumerate();
+
+    for boundary in boundaries {
+        if let Some((_, byte_idx)) = char_indices.find(|(char_idx, _)| *char_idx == boundary) {
+            byte_indices.insert(boundary, byte_idx);
+        }
+    }
+
+    // apply from the back so the byte indices of earlier suggestions stay valid
+    for suggestion in suggestions.iter().rev() {
+        if let Some(best) = suggestion.best() {
+            let start = byte_indices[&suggestion.start];
+            let end = byte_indices[&suggestion.end];
+
+            text.replace_range(start..end, best);
+        }
+    }
+
+    Ok(())
+}
+
+/// Translates the offsets of suggestions computed for a derived text (e. g. after Unicode normalization) to the original text.
+/// `char_map[i]` must be the char index in the original text which the char `i` of the derived text stems from.
+/// `char_map` must have one more element than the derived text has chars, containing the char length of the original text.
+pub fn remap_suggestions(suggestions: &[Suggestion], char_map: &[usize]) -> Vec<Suggestion> {
+    suggestions
+        .iter()
+        .map(|x| {
+            let mut x = x.clone();
+            x.start = char_map[x.start];
+            x.end = char_map[x.end];
+            x
+        })
+        .collect()
+}
+
+/// Corrects only the part of a text between the char indices `start` (inclusive) and `end` (exclusive),
+/// e. g. the visible part of a document. Only suggestions which lie entirely within the range are applied.
+/// The range is clamped to the text, if `start` is after `end` the range is empty.
+///
+/// Returns the corrected part of the text and its length in chars.
+pub fn correct_range(
+    text: &str,
+    suggestions: &[Suggestion],
+    start: usize,
+    end: usize,
+) -> (String, usize) {
+    let end = end.min(text.chars().count());
+    let start = start.min(end);
+
+    let part: String = text.chars().skip(start).take(end - start).collect();
+    let suggestions: Vec<_> = suggestions
+        .iter()
+        .filter(|x| x.start >= start && x.end <= end)
+        .map(|x| {
+            let mut x = x.clone();
+            x.start -= start;
+            x.end -= start;
+            x
+        })
+        .collect();
+
+    let corrected = apply_suggestions(&part, &suggestions);
+    let length = corrected.chars().count();
+
+    (corrected, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing;
+
+    fn suggestion(start: usize, end: usize, replacement: &str) -> Suggestion {
+        Suggestion {
+            source: "_Test".into(),
+            message: "_Test".into(),
+            start,
+            end,
+            replacements: vec![replacement.into()],
+            confidence: None,
+            sentence_index: 0,
+        }
+    }
+
+    fn replacements(suggestions: &[Suggestion]) -> Vec<String> {
+        suggestions
+            .iter()
+            .filter_map(|x| x.best())
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn can_insert() {
+        let text = "Hello world and you.";
+        let suggestions = vec![suggestion(5, 5, ","), suggestion(11, 11, ",")];
+
+        assert_eq!(
+            apply_suggestions(text, &suggestions),
+            "Hello, world, and you."
+        );
+    }
+
+    #[test]
+    fn can_correct_range() {
+        let text = "Its a nice day. Its sunny.";
+        let suggestions = vec![
+            suggestion(0, 3, "It's"),
+            suggestion(14, 17, ""),
+            suggestion(16, 19, "It's"),
+        ];
+
+        assert_eq!(
+            correct_range(text, &suggestions, 0, 15),
+            ("It's a nice day.".to_string(), 16)
+        );
+        assert_eq!(
+            correct_range(text, &suggestions, 16, 26),
+            ("It's sunny.".to_string(), 11)
+        );
+        assert_eq!(
+            correct_range(text, &suggestions, 16, 100),
+            ("It's sunny.".to_string(), 11)
+        );
+        assert_eq!(correct_range(text, &suggestions, 15, 3), (String::new(), 0));
+        assert_eq!(
+            correct_range(text, &suggestions, 30, 40),
+            (String::new(), 0)
+        );
+    }
+
+    #[test]
+    fn can_correct_in_place() {
+        let text = "Its a nice dya. Ths is is ümlaut.";
+        let suggestions = vec![
+            suggestion(0, 3, "It's"),
+            suggestion(11, 14, "day"),
+            suggestion(16, 19, "This"),
+            suggestion(20, 23, ""),
+            suggestion(26, 26, "an "),
+        ];
+
+        let mut corrected = text.to_string();
+        correct_in_place(&mut corrected, &suggestions).unwrap();
+
+        assert_eq!(corrected, apply_suggestions(text, &suggestions));
+        assert_eq!(corrected, "It's a nice day. This is an ümlaut.");
+    }
+
+    #[test]
+    fn correct_in_place_rejects_invalid_suggestions() {
+        let text = "Its a nice dya.";
+
+        for (suggestions, error) in &[
+            (
+                vec![suggestion(11, 14, "day"), suggestion(11, 20, "day.")],
+                ValidationError::InvalidSpan { index: 1 },
+            ),
+            (
+                vec![suggestion(11, 14, "day"), suggestion(0, 3, "It's")],
+                ValidationError::Overlap { index: 1 },
+            ),
+            (
+                vec![suggestion(0, 5, "It's a"), suggestion(3, 5, "")],
+                ValidationError::Overlap { index: 1 },
+            ),
+        ] {
+            let mut corrected = text.to_string();
+            assert_eq!(
+                correct_in_place(&mut corrected, suggestions),
+                Err(error.clone())
+            );
+            assert_eq!(corrected, text);
+        }
+    }
+
+    #[test]
+    fn can_preserve_case() {
+        let text = "Teh cat. THSI is monady, not MONADY.";
+        let suggestions = vec![
+            suggestion(0, 3, "the"),
+            suggestion(9, 13, "this"),
+            suggestion(17, 23, "Monday"),
+            suggestion(29, 35, "Monday"),
+        ];
+
+        assert_eq!(
+            apply_suggestions_preserving_case(text, &suggestions),
+            "The cat. THIS is Monday, not MONDAY."
+        );
+        assert_eq!(
+            apply_suggestions_preserving_case("monday", &[suggestion(0, 6, "Monday")]),
+            "Monday"
+        );
+        assert_eq!(
+            apply_suggestions(text, &suggestions),
+            "the cat. this is Monday, not Monday."
+        );
+    }
+
+    #[test]
+    fn reports_applied_suggestions() {
+        let text = "Its a nice dya.";
+        let suggestions = vec![
+            suggestion(11, 14, "day"),
+            suggestion(0, 3, "It's"),
+            suggestion(2, 5, "s a"),
+            suggestion(14, 20, "!"),
+        ];
+
+        let (corrected, applied) = correct_verbose(text, &suggestions);
+
+        assert_eq!(corrected, "It's a nice day.");
+        assert_eq!(applied, vec![0, 1]);
+    }
+
+    #[test]
+    fn can_get_line_col() {
+        let text = "Its a test.\r\nÜmlaut dya.\nEnd";
+
+        assert_eq!(line_col(text, 0), (1, 1));
+        assert_eq!(line_col(text, 11), (1, 12));
+        assert_eq!(line_col(text, 12), (1, 12));
+        assert_eq!(line_col(text, 13), (2, 1));
+        assert_eq!(line_col(text, 20), (2, 8));
+        assert_eq!(line_col(text, 25), (3, 1));
+
+        assert_eq!(suggestion(20, 23, "day").location(text), ((2, 8), (2, 11)));
+    }
+
+    #[test]
+    fn can_validate_suggestions() {
+        let text = "Its a nice dya.";
+
+        assert!(suggestion(0, 3, "It's").is_valid_for(text));
+        assert!(suggestion(15, 15, "!").is_valid_for(text));
+        assert!(!suggestion(11, 16, "day").is_valid_for(text));
+        assert!(!suggestion(3, 2, "").is_valid_for(text));
+
+        assert_eq!(
+            validate_suggestions(text, &[suggestion(0, 3, "It's"), suggestion(11, 14, "day")]),
+            Ok(())
+        );
+        assert_eq!(
+            validate_suggestions(text, &[suggestion(0, 3, "It's"), suggestion(11, 20, "day")]),
+            Err(ValidationError::InvalidSpan { index: 1 })
+        );
+        assert_eq!(
+            validate_suggestions(text, &[suggestion(0, 5, "It's"), suggestion(4, 6, "a")]),
+            Err(ValidationError::Overlap { index: 1 })
+        );
+        assert_eq!(
+            validate_suggestions(text, &[suggestion(11, 14, "day"), suggestion(0, 3, "It's")]),
+            Err(ValidationError::Overlap { index: 1 })
+        );
+    }
+
+    #[test]
+    fn splits_suggestions_by_sentence() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let text = "This is a test.  Another one! And a third";
+        let sentences = Rules::default().suggest_by_sentence(text, &tokenizer);
+
+        assert_eq!(
+            sentences,
+            vec![
+                ("This is a test.".to_string(), Vec::new()),
+                ("Another one!".to_string(), Vec::new()),
+                ("And a third".to_string(), Vec::new()),
+            ]
+        );
+        assert_eq!(
+            Rules::default().suggest_by_sentence_with_context(text, &tokenizer, 2),
+            sentences
+        );
+    }
+
+    #[test]
+    fn none_can_trigger_without_trigger_words() {
+        use crate::rule::engine::{composition::Atom, Engine};
+
+        let tagger = testing::tagger(&[]);
+        let rule = testing::rule("TEH", "teh", "the");
+        let rules = [(0, &rule)];
+
+        assert!(Rules::none_can_trigger(
+            &rules,
+            &testing::tokenize("I like cake", &tagger)
+        ));
+        assert!(!Rules::none_can_trigger(
+            &rules,
+            &testing::tokenize("I like Teh cake", &tagger)
+        ));
+
+        // a rule which can match the empty sentence start token has no trigger words
+        let mut empty_rule = testing::rule("EMPTY", "teh", "the");
+        if let Engine::Token(engine) = &mut empty_rule.engine {
+            if let Atom::TextAtom(atom) = &mut engine.composition.parts[0].atom {
+                atom.matcher.matcher.empty_match = Some(true);
+            }
+        }
+
+        assert!(!Rules::none_can_trigger(
+            &[(0, &rule), (1, &empty_rule)],
+            &testing::tokenize("I like cake", &tagger)
+        ));
+    }
+
+    #[test]
+    fn context_suggestions_do_not_suppress_suggestions() {
+        let tokenizer = testing::tokenizer(&[]);
+        let mut rules = Rules::default();
+        rules.push(testing::rule("WE", "We", "They"));
+        rules.push(testing::phrase_rule("JOINED", &[".", "We"], "; we"));
+
+        let text = "I ate cake. We ate pie.";
+        // across the whole text, the suggestion spanning the sentence boundary wins
+        assert_eq!(
+            rules
+                .suggest(text, &tokenizer)
+                .into_iter()
+                .map(|x| x.source)
+                .collect::<Vec<_>>(),
+            vec!["JOINED"]
+        );
+
+        let sentences = rules.suggest_by_sentence_with_context(text, &tokenizer, 1);
+        assert!(sentences[0].1.is_empty());
+        assert_eq!(
+            sentences[1]
+                .1
+                .iter()
+                .map(|x| (x.source.as_str(), x.start, x.sentence_index))
+                .collect::<Vec<_>>(),
+            vec![("WE", 0, 1)]
+        );
+    }
+
+    #[test]
+    fn first_match_only_skips_matches_without_replacements() {
+        use crate::rule::{
+            engine::{
+                composition::{OrAtom, PosMatcher},
+                Engine, TokenEngine,
+            },
+            grammar::{Conversion, Match, PosReplacer, Synthesizer, SynthesizerPart},
+        };
+
+        let tagger = testing::tagger_from_dump(&["went\tgo\tVBD", "go\tgo\tVB"]);
+        let vb = *tagger.id_tag("VB").id();
+        let tokenizer = Tokenizer {
+            tagger: Arc::new(tagger),
+            ..Tokenizer::default()
+        };
+
+        // inflects the matched verb to "VB", which is not possible for "walked" since it is not in the dictionary
+        let mut rule = testing::rule("INFINITIVE", "", "");
+        rule.engine = Engine::Token(TokenEngine {
+            composition: testing::composition(vec![OrAtom {
+                atoms: vec![testing::text_atom("walked"), testing::text_atom("went")],
+            }
+            .into()]),
+            antipatterns: Vec::new(),
+        });
+        rule.suggesters = vec![Synthesizer {
+            use_titlecase_adjust: false,
+            sentence_case: false,
+            parts: vec![SynthesizerPart::Match(Match {
+                id: 1,
+                conversion: Conversion::Nop,
+                pos_replacer: Some(PosReplacer {
+                    matcher: PosMatcher {
+                        mask: (0..16).map(|i| i == vb).collect(),
+                    },
+                }),
+                regex_replacer: None,
+            })],
+        }];
+        rule.set_first_match_only(true);
+
+        let mut rules = Rules::default();
+        rules.push(rule);
+
+        let suggestions = rules.suggest("I walked and went home.", &tokenizer);
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|x| (x.start, x.replacements.clone()))
+                .collect::<Vec<_>>(),
+            vec![(13, vec!["go".to_string()])]
+        );
+    }
+
+    #[test]
+    fn first_match_only_applies_per_sentence() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let mut rule = testing::rule("ATE", "ate", "eat");
+        rule.set_first_match_only(true);
+
+        let mut rules = Rules::default();
+        rules.push(rule);
+
+        let suggestions =
+            rules.suggest("I ate and ate. You ate. Nothing here. We ate.", &tokenizer);
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|x| (x.start, x.sentence_index))
+                .collect::<Vec<_>>(),
+            vec![(2, 0), (19, 1), (41, 3)]
+        );
+    }
+
+    #[test]
+    fn serializes_deterministically() {
+        use crate::rule::engine::{
+            composition::{concrete, DictionaryMatcher, SetMatcher},
+            Engine, TokenEngine,
+        };
+        use crate::utils::regex::SerializeRegex;
+
+        // every set and map has its own random hasher state, so the iteration order differs between builds
+        fn build() -> Rules {
+            let words: Vec<_> = (0..100).map(|x| format!("word{}", x)).collect();
+
+            let chunk_rule = {
+                let mut rule = testing::rule("CHUNK", "", "");
+                rule.engine = Engine::Token(TokenEngine {
+                    composition: testing::composition(vec![concrete::ChunkSetAtom {
+                        matcher: SetMatcher {
+                            set: words.iter().cloned().collect(),
+                            negate: true,
+                        },
+                    }
+                    .into()]),
+                    antipatterns: Vec::new(),
+                });
+                rule
+            };
+
+            let dictionary_rule = {
+                let mut rule = testing::rule("DICTIONARY", "", "");
+                rule.engine = Engine::Token(TokenEngine {
+                    composition: testing::composition(vec![concrete::DictionaryAtom {
+                        matcher: DictionaryMatcher {
+                            words: Arc::new(words.iter().cloned().collect()),
+                            case_sensitive: false,
+                        },
+                    }
+                    .into()]),
+                    antipatterns: Vec::new(),
+                });
+                rule
+            };
+
+            let text_rule = {
+                let mut rule = testing::rule("TEXT", "", "");
+                rule.engine = Engine::Text(
+                    SerializeRegex::new(r"(a)(b)(c)", false, true).unwrap(),
+                    (0..100).map(|x| (x, x * 2)).collect(),
+                );
+                rule
+            };
+
+            let mut rules = Rules::default();
+            rules.push(chunk_rule);
+            rules.push(dictionary_rule);
+            rules.push(text_rule);
+            rules
+        }
+
+        let bytes = bincode::serialize(&build()).unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(bincode::serialize(&build()).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn sets_sentence_indices() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let text = "Its a nice dya. Thsi is is it. Ümlaut is nice.";
+        let mut suggestions = [
+            suggestion(0, 3, "It's"),
+            suggestion(11, 14, "day"),
+            suggestion(16, 20, "This"),
+            suggestion(31, 37, "Umlaut"),
+        ];
+        let tokenized = Tokenized::new(text, &tokenizer);
+        let starts = tokenized.sentence_starts(&tokenizer);
+        assert_eq!(starts, tokenized.tokens().sentence_starts(&tokenizer));
+
+        set_sentence_indices(suggestions.iter_mut(), &starts);
+
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|x| x.sentence_index)
+                .collect::<Vec<_>>(),
+            vec![0, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "profile")]
+    fn sets_sentence_indices_when_profiling() {
+        let tokenizer = testing::tokenizer(&[]);
+        let mut rules = Rules::default();
+        rules.push(testing::rule("TEH", "teh", "the"));
+
+        let (suggestions, timings) =
+            rules.suggest_profiled("I like teh cake. We ate teh pie.", &tokenizer);
+
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|x| (x.start, x.sentence_index))
+                .collect::<Vec<_>>(),
+            vec![(7, 0), (24, 1)]
+        );
+        assert!(timings.contains_key("TEH"));
+    }
+
+    #[test]
+    fn adds_synonyms_of_single_words() {
+        struct Thesaurus;
+
+        impl SynonymProvider for Thesaurus {
+            fn synonyms(&self, word: &str) -> Vec<String> {
+                match word {
+                    "big" => vec!["large".into(), "huge".into()],
+                    "huge" => vec!["big".into(), "enormous".into()],
+                    _ => Vec::new(),
+                }
+            }
+        }
+
+        let mut single = suggestion(0, 3, "big");
+        single.replacements.push("huge".into());
+        add_synonyms(&mut single, &Thesaurus);
+        assert_eq!(
+            single.replacements,
+            vec!["big", "huge", "large", "enormous"]
+        );
+
+        let mut phrase = suggestion(0, 7, "big dog");
+        add_synonyms(&mut phrase, &Thesaurus);
+        assert_eq!(phrase.replacements, vec!["big dog"]);
+    }
+
+    #[test]
+    fn maps_severity_from_category_type() {
+        assert_eq!(
+            Severity::from_category_type(Some("misspelling")),
+            Severity::Error
+        );
+        assert_eq!(
+            Severity::from_category_type(Some("style")),
+            Severity::Information
+        );
+        assert_eq!(
+            Severity::from_category_type(Some("grammar")),
+            Severity::Warning
+        );
+        assert_eq!(Severity::from_category_type(None), Severity::Warning);
+    }
+
+    #[test]
+    fn empty_rules_complete_before_deadline() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        let (suggestions, complete) =
+            Rules::default().suggest_with_deadline("A test.", &tokenizer, deadline);
+
+        assert!(suggestions.is_empty());
+        assert!(complete);
+    }
+
+    #[test]
+    fn annotates_all_tokens() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let annotations = Rules::default().annotate("A test.", &tokenizer);
+
+        assert_eq!(
+            annotations
+                .iter()
+                .map(|x| (x.text.as_str(), x.char_span))
+                .collect::<Vec<_>>(),
+            vec![("A", (0, 1)), ("test", (2, 6)), (".", (6, 7))]
+        );
+        assert_eq!(annotations[0].pos, vec!["UNKNOWN"]);
+        assert_eq!(annotations[2].pos, vec!["UNKNOWN", "SENT_END"]);
+        assert!(annotations.iter().all(|x| x.rule_ids.is_empty()));
+    }
+
+    #[test]
+    fn can_remap_suggestions() {
+        // "e\u{301}" is normalized to "é"
+        let original = "Cafe\u{301} nce.";
+        let normalized = "Café nce.";
+        let char_map = vec![0, 1, 2, 3, 5, 6, 7, 8, 9, 10];
+
+        let suggestions = vec![suggestion(5, 8, "once")];
+        assert_eq!(apply_suggestions(normalized, &suggestions), "Café once.");
+
+        let remapped = remap_suggestions(&suggestions, &char_map);
+        assert_eq!((remapped[0].start, remapped[0].end), (6, 9));
+        assert_eq!(apply_suggestions(original, &remapped), "Cafe\u{301} once.");
+    }
+
+    #[test]
+    fn can_build_options() {
+        let options = RulesOptions::default()
+            .with_ids(vec!["A", "B"])
+            .ignoring(vec!["C".to_string()])
+            .allow_errors(false);
+
+        assert_eq!(options.ids, vec!["A", "B"]);
+        assert_eq!(options.ignore_ids, vec!["C"]);
+        assert!(!options.allow_errors);
+    }
+
+    #[test]
+    fn can_delete() {
+        let text = "This is is a a test.";
+        let suggestions = vec![suggestion(5, 8, ""), suggestion(10, 12, "")];
+
+        assert_eq!(apply_suggestions(text, &suggestions), "This is a test.");
+    }
+
+    #[test]
+    fn skips_suggestions_without_replacements() {
+        let text = "Its a nice dya.";
+        let mut suggestions = vec![suggestion(0, 3, "It's"), suggestion(11, 14, "day")];
+        suggestions[0].replacements.clear();
+        assert_eq!(suggestions[0].best(), None);
+        assert_eq!(suggestions[1].best(), Some("day"));
+
+        assert_eq!(apply_suggestions(text, &suggestions), "Its a nice day.");
+        assert_eq!(
+            apply_suggestions_preserving_case(text, &suggestions),
+            "Its a nice day."
+        );
+
+        let mut corrected = text.to_string();
+        correct_in_place(&mut corrected, &suggestions).unwrap();
+        assert_eq!(corrected, "Its a nice day.");
+    }
+
+    #[test]
+    fn reports_suppressed_overlaps() {
+        let output = vec![
+            (1, suggestion(0, 4, "a")),
+            (0, suggestion(2, 6, "b")),
+            (2, suggestion(2, 2, "c")),
+            (3, suggestion(6, 8, "d")),
+        ];
+
+        let (kept, suppressed) = resolve_overlaps(output, 10, OverlapStrategy::FirstWins, |_| 0);
+
+        assert_eq!(replacements(&kept), vec!["a", "d"]);
+        assert_eq!(replacements(&suppressed), vec!["c", "b"]);
+    }
+
+    #[test]
+    fn ranks_by_priority_confidence_and_length() {
+        let with_confidence = |start, end, replacement, confidence| Suggestion {
+            confidence,
+            ..suggestion(start, end, replacement)
+        };
+
+        let output = vec![
+            (0, with_confidence(0, 2, "a", Some(0.9))),
+            (0, with_confidence(3, 8, "b", None)),
+            (0, with_confidence(10, 12, "c", Some(0.5))),
+            (0, with_confidence(14, 20, "d", Some(0.5))),
+            (1, with_confidence(22, 23, "e", None)),
+            (0, with_confidence(24, 26, "f", Some(0.5))),
+        ];
+
+        let ranked = rank(output, |i| if i == 1 { 10 } else { 0 });
+        assert_eq!(replacements(&ranked), vec!["e", "a", "d", "c", "f", "b"]);
+    }
+
+    #[test]
+    fn later_rules_win_ties() {
+        let tokenizer = testing::tokenizer(&[]);
+        let text = "I like teh cake.";
+        let sources = |rules: &Rules| {
+            rules
+                .suggest(text, &tokenizer)
+                .into_iter()
+                .map(|x| x.source)
+                .collect::<Vec<_>>()
+        };
+
+        let mut rules = Rules::default();
+        rules.push(testing::rule("EXISTING", "teh", "the"));
+        rules.push(testing::rule("PUSHED", "teh", "tea"));
+        assert_eq!(sources(&rules), vec!["PUSHED"]);
+
+        rules.rules[0].set_priority(1);
+        assert_eq!(sources(&rules), vec!["EXISTING"]);
+
+        let mut first = Rules::default();
+        first.push(testing::rule("FIRST", "teh", "the"));
+        let mut second = Rules::default();
+        second.push(testing::rule("SECOND", "teh", "tea"));
+
+        let merged = first.merge(second);
+        assert_eq!(
+            merged.rules().iter().map(|x| x.id()).collect::<Vec<_>>(),
+            vec!["FIRST", "SECOND"]
+        );
+        assert_eq!(sources(&merged), vec!["SECOND"]);
+    }
+
+    #[test]
+    fn resolves_overlaps_by_strategy() {
+        let output = vec![
+            (2, suggestion(0, 1, "a")),
+            (1, suggestion(0, 6, "b")),
+            (0, suggestion(4, 7, "c")),
+            (3, suggestion(6, 10, "d")),
+        ];
+
+        let (kept, _) = resolve_overlaps(output.clone(), 10, OverlapStrategy::FirstWins, |_| 0);
+        assert_eq!(replacements(&kept), vec!["a", "c"]);
+
+        let (kept, _) = resolve_overlaps(output.clone(), 10, OverlapStrategy::LongestWins, |_| 0);
+        assert_eq!(replacements(&kept), vec!["b", "d"]);
+
+        let (kept, suppressed) = resolve_overlaps(output, 10, OverlapStrategy::PriorityWins, |_| 0);
+        assert_eq!(replacements(&kept), vec!["b", "d"]);
+        assert_eq!(replacements(&suppressed), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn priority_wins_prefers_priority_then_length_then_rule_order() {
+        let resolve = |output: &[(usize, Suggestion)], strategy, priorities: [i32; 2]| {
+            replacements(&resolve_overlaps(output.to_vec(), 10, strategy, |i| priorities[i]).0)
+        };
+
+        // the longer suggestion wins although it starts later
+        let output = [(0, suggestion(0, 2, "a")), (1, suggestion(1, 6, "b"))];
+        assert_eq!(
+            resolve(&output, OverlapStrategy::FirstWins, [0, 0]),
+            vec!["a"]
+        );
+        assert_eq!(
+            resolve(&output, OverlapStrategy::PriorityWins, [0, 0]),
+            vec!["b"]
+        );
+
+        // unlike with `LongestWins`, the priority comes before the length
+        assert_eq!(
+            resolve(&output, OverlapStrategy::LongestWins, [1, 0]),
+            vec!["b"]
+        );
+        assert_eq!(
+            resolve(&output, OverlapStrategy::PriorityWins, [1, 0]),
+            vec!["a"]
+        );
+
+        // for spans with the same length, the rule which comes first wins
+        let output = [(1, suggestion(0, 3, "x")), (0, suggestion(2, 5, "y"))];
+        assert_eq!(
+            resolve(&output, OverlapStrategy::FirstWins, [0, 0]),
+            vec!["x"]
+        );
+        assert_eq!(
+            resolve(&output, OverlapStrategy::PriorityWins, [0, 0]),
+            vec!["y"]
+        );
+    }
+
+    #[test]
+    fn higher_priority_wins_overlaps() {
+        let output = vec![
+            (0, suggestion(0, 4, "a")),
+            (1, suggestion(2, 6, "b")),
+            (2, suggestion(6, 8, "c")),
+        ];
+        let priorities = [0, 1, 0];
+
+        for strategy in [OverlapStrategy::FirstWins, OverlapStrategy::PriorityWins] {
+            let (kept, suppressed) =
+                resolve_overlaps(output.clone(), 10, strategy, |i| priorities[i]);
+            assert_eq!(replacements(&kept), vec!["b", "c"]);
+            assert_eq!(replacements(&suppressed), vec!["a"]);
+        }
+
+        let (kept, _) = resolve_overlaps(output, 10, OverlapStrategy::FirstWins, |_| 0);
+        assert_eq!(replacements(&kept), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn updates_suggestions_after_edit() {
+        let tokenizer = testing::tokenizer(&[]);
+        let mut rules = Rules::default();
+        rules.push(testing::rule("TEH", "teh", "the"));
+        rules.push(testing::rule("HAS", "has", "have"));
+
+        let text = "I has teh cake. We ate teh pie.";
+        let suggestions = rules.suggest(text, &tokenizer);
+        assert_eq!(replacements(&suggestions), vec!["have", "the", "the"]);
+
+        let edited = apply_one(text, &suggestions[0]);
+        let updated = rules.update_suggestions(&edited, &suggestions, &suggestions[0], &tokenizer);
+        assert_eq!(
+            updated
+                .iter()
+                .map(|x| (x.start, x.end, x.sentence_index))
+                .collect::<Vec<_>>(),
+            vec![(7, 10, 0), (24, 27, 1)]
+        );
+        assert_eq!(updated, rules.suggest(&edited, &tokenizer));
+
+        // a suggestion without replacements does not change the text
+        let mut empty = suggestions[0].clone();
+        empty.replacements.clear();
+        assert_eq!(
+            rules.update_suggestions(text, &suggestions, &empty, &tokenizer),
+            suggestions
+        );
+    }
+
+    #[test]
+    fn shifts_suggestions_after_edit() {
+        let suggestions = vec![
+            suggestion(0, 3, "It's"),
+            suggestion(4, 6, ""),
+            suggestion(10, 12, "x"),
+        ];
+
+        let shifted = shift_suggestions(&suggestions, &suggestions[1], -2);
+
+        assert_eq!(shifted.len(), 2);
+        assert_eq!((shifted[0].start, shifted[0].end), (0, 3));
+        assert_eq!((shifted[1].start, shifted[1].end), (8, 10));
+    }
+
+    #[test]
+    fn can_mix_insert_delete_and_replace() {
+        let text = "Its a a nice day";
+        let suggestions = vec![
+            suggestion(0, 3, "It's"),
+            suggestion(4, 6, ""),
+            suggestion(16, 16, "."),
+        ];
+
+        assert_eq!(apply_suggestions(text, &suggestions), "It's a nice day.");
+    }
+
+    #[test]
+    fn can_diff_from_suggestions() {
+        let text = "Its a a nice day";
+        let suggestions = vec![
+            suggestion(16, 16, "."),
+            suggestion(0, 3, "It's"),
+            suggestion(4, 6, ""),
+        ];
+
+        assert_eq!(
+            suggestions_to_diff(text, &suggestions),
+            vec![
+                DiffOp::Delete("Its".into()),
+                DiffOp::Insert("It's".into()),
+                DiffOp::Equal(" ".into()),
+                DiffOp::Delete("a ".into()),
+                DiffOp::Equal("a nice day".into()),
+                DiffOp::Insert(".".into()),
+            ]
+        );
+    }
+}
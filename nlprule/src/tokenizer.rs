@@ -5,13 +5,19 @@
 //! [DisambiguationRule][crate::rule::DisambiguationRule]s.
 
 use crate::{types::*, utils::parallelism::MaybeParallelRefIterator};
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
 use lazy_static::lazy_static;
+#[cfg(feature = "regex")]
 use onig::Regex;
+#[cfg(all(feature = "regex-pure", not(feature = "regex")))]
+use regex_crate::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
     collections::HashSet,
     fs::File,
     io::{BufReader, Read},
+    ops::Deref,
     path::Path,
     sync::Arc,
 };
@@ -46,18 +52,49 @@ where
     result
 }
 
+/// Splits the text into token strings. URLs are kept as one token if the `regex` or `regex-pure` feature is enabled.
 fn get_token_strs(text: &str) -> Vec<&str> {
-    let mut tokens = Vec::new();
+    let split_func = |c: char| c.is_whitespace() || crate::utils::splitting_chars().contains(c);
+
+    #[cfg(not(any(feature = "regex", feature = "regex-pure")))]
+    {
+        split(text, split_func)
+    }
 
+    #[cfg(any(feature = "regex", feature = "regex-pure"))]
+    {
+        get_token_strs_with_urls(text, split_func)
+    }
+}
+
+/// Gets the byte spans of URLs in the text.
+#[cfg(feature = "regex")]
+fn url_spans(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
     lazy_static! {
         // see https://stackoverflow.com/a/17773849
         static ref URL_REGEX: Regex = Regex::new(r"(https?:\/\/(?:www\.|(?!www))[a-zA-Z0-9][a-zA-Z0-9-]+[a-zA-Z0-9]\.[^\s]{2,}|www\.[a-zA-Z0-9][a-zA-Z0-9-]+[a-zA-Z0-9]\.[^\s]{2,}|https?:\/\/(?:www\.|(?!www))[a-zA-Z0-9]+\.[^\s]{2,}|www\.[a-zA-Z0-9]+\.[^\s]{2,})").unwrap();
     }
 
+    URL_REGEX.find_iter(text)
+}
+
+/// Gets the byte spans of URLs in the text. The `regex` crate does not support lookahead, so unlike with Oniguruma
+/// a URL such as `http://wwwexample.com` is not excluded.
+#[cfg(all(feature = "regex-pure", not(feature = "regex")))]
+fn url_spans(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    lazy_static! {
+        static ref URL_REGEX: Regex = Regex::new(r"(https?://(?:www\.)?[a-zA-Z0-9][a-zA-Z0-9-]+[a-zA-Z0-9]\.[^\s]{2,}|www\.[a-zA-Z0-9][a-zA-Z0-9-]+[a-zA-Z0-9]\.[^\s]{2,}|https?://(?:www\.)?[a-zA-Z0-9]+\.[^\s]{2,}|www\.[a-zA-Z0-9]+\.[^\s]{2,})").unwrap();
+    }
+
+    URL_REGEX.find_iter(text).map(|x| (x.start(), x.end()))
+}
+
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
+fn get_token_strs_with_urls<F: Fn(char) -> bool + Copy>(text: &str, split_func: F) -> Vec<&str> {
+    let mut tokens = Vec::new();
     let mut prev = 0;
-    let split_func = |c: char| c.is_whitespace() || crate::utils::splitting_chars().contains(c);
 
-    for (start, end) in URL_REGEX.find_iter(text) {
+    for (start, end) in url_spans(text) {
         tokens.extend(split(&text[prev..start], split_func));
         tokens.push(&text[start..end]);
         prev = end;
@@ -68,6 +105,21 @@ fn get_token_strs(text: &str) -> Vec<&str> {
     tokens
 }
 
+/// Gets the char span of the text covered by the tokens, e. g. to show the whole sentence a suggestion occurs in.
+/// Does not include whitespace before the first or after the last token. Returns `(0, 0)` if there are no tokens
+/// (the special sentence start token is ignored).
+pub fn sentence_span(tokens: &[Token]) -> (usize, usize) {
+    tokens
+        .iter()
+        .filter(|x| x.char_span.0 < x.char_span.1)
+        .fold(None, |span: Option<(usize, usize)>, x| {
+            Some(span.map_or(x.char_span, |(start, end)| {
+                (start.min(x.char_span.0), end.max(x.char_span.1))
+            }))
+        })
+        .unwrap_or((0, 0))
+}
+
 /// *Finalizes* the tokens by e. g. adding a specific UNKNOWN part-of-speech tag.
 /// After finalization grammatical error correction rules can be used on the tokens.
 pub fn finalize(tokens: Vec<IncompleteToken>) -> Vec<Token> {
@@ -81,6 +133,131 @@ pub fn finalize(tokens: Vec<IncompleteToken>) -> Vec<Token> {
     finalized
 }
 
+/// Gets the char index of the start of each sentence given the byte spans of the sentences.
+fn sentence_char_starts(text: &str, spans: &[(usize, usize)]) -> Vec<usize> {
+    let mut n_chars = 0;
+    let mut prev_byte = 0;
+
+    spans
+        .iter()
+        .map(|(start, _)| {
+            n_chars += text[prev_byte..*start].chars().count();
+            prev_byte = *start;
+            n_chars
+        })
+        .collect()
+}
+
+/// Gets the byte span of the sentences overlapping with the bytes from `byte_start` to `byte_end`
+/// given the byte spans of all sentences of the text.
+pub(crate) fn sentence_range(
+    spans: &[(usize, usize)],
+    text: &str,
+    byte_start: usize,
+    byte_end: usize,
+) -> (usize, usize) {
+    let start = spans
+        .iter()
+        .rev()
+        .find(|(start, _)| *start <= byte_start)
+        .map_or(0, |x| x.0);
+    let end = spans
+        .iter()
+        .find(|(_, end)| *end >= byte_end)
+        .map_or(text.len(), |x| x.1);
+
+    (start, end)
+}
+
+/// Finalized tokens which rules can be applied to, see [apply][crate::Rules::apply]. Implemented for slices and vectors
+/// of tokens and for [Tokenized], which also stores the sentence segmentation of its text.
+pub trait AsTokens {
+    /// Gets the finalized tokens.
+    fn as_tokens(&self) -> &[Token<'_>];
+
+    /// Gets the char index of the start of each sentence of the text the tokens are from.
+    fn sentence_starts(&self, tokenizer: &Tokenizer) -> Cow<'_, [usize]>;
+}
+
+impl<'t> AsTokens for [Token<'t>] {
+    fn as_tokens(&self) -> &[Token<'_>] {
+        self
+    }
+
+    fn sentence_starts(&self, tokenizer: &Tokenizer) -> Cow<'_, [usize]> {
+        match self.first() {
+            Some(token) => Cow::Owned(tokenizer.sentence_starts(token.text)),
+            None => Cow::Borrowed(&[]),
+        }
+    }
+}
+
+impl<'t> AsTokens for Vec<Token<'t>> {
+    fn as_tokens(&self) -> &[Token<'_>] {
+        self
+    }
+
+    fn sentence_starts(&self, tokenizer: &Tokenizer) -> Cow<'_, [usize]> {
+        self.as_slice().sentence_starts(tokenizer)
+    }
+}
+
+/// The finalized tokens of a text, e. g. to check the same text with different subsets of rules without tokenizing it again.
+/// Can be passed to [apply][crate::Rules::apply] and its variants:
+///
+/// ```ignore
+/// let tokenized = Tokenized::new(text, &tokenizer);
+///
+/// let grammar = rules.apply_with_filter(&tokenized, &tokenizer, |x| x.category_id() == "GRAMMAR");
+/// let style = rules.apply_with_filter(&tokenized, &tokenizer, |x| x.category_id() == "STYLE");
+/// ```
+pub struct Tokenized<'t> {
+    text: &'t str,
+    tokens: Vec<Token<'t>>,
+    sentence_starts: Vec<usize>,
+}
+
+impl<'t> Tokenized<'t> {
+    /// Tokenizes, disambiguates and [finalizes][finalize] the text, i. e. does the same work as [suggest][crate::Rules::suggest] before checking rules.
+    pub fn new(text: &'t str, tokenizer: &'t Tokenizer) -> Self {
+        let spans = tokenizer.sentence_spans(text);
+
+        Tokenized {
+            text,
+            tokens: finalize(tokenizer.disambiguate(tokenizer.tokenize_sentences(text, &spans))),
+            sentence_starts: sentence_char_starts(text, &spans),
+        }
+    }
+
+    /// The text the tokens are from.
+    pub fn text(&self) -> &'t str {
+        self.text
+    }
+
+    /// The finalized tokens.
+    pub fn tokens(&self) -> &[Token<'t>] {
+        &self.tokens
+    }
+}
+
+impl<'t> AsTokens for Tokenized<'t> {
+    fn as_tokens(&self) -> &[Token<'_>] {
+        &self.tokens
+    }
+
+    fn sentence_starts(&self, _tokenizer: &Tokenizer) -> Cow<'_, [usize]> {
+        Cow::Borrowed(&self.sentence_starts)
+    }
+}
+
+impl<'t> Deref for Tokenized<'t> {
+    type Target = [Token<'t>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.tokens
+    }
+}
+
 /// Options for a tokenizer.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TokenizerOptions {
@@ -105,6 +282,9 @@ pub struct TokenizerOptions {
     /// Used part-of-speech tags which are not in the tagger dictionary.
     #[serde(default)]
     pub extra_tags: Vec<String>,
+    /// Abbreviations (including the trailing period e. g. `Fig.`) which must not end a sentence.
+    #[serde(default)]
+    pub abbreviations: HashSet<String>,
 }
 
 impl Default for TokenizerOptions {
@@ -118,6 +298,7 @@ impl Default for TokenizerOptions {
             ignore_ids: Vec::new(),
             known_failures: Vec::new(),
             extra_tags: Vec::new(),
+            abbreviations: HashSet::new(),
         }
     }
 }
@@ -159,10 +340,44 @@ impl Tokenizer {
         &self.options
     }
 
+    /// Gets the abbreviations after which a sentence must not end, see [with_abbreviations][Tokenizer::with_abbreviations].
+    pub fn abbreviations(&self) -> &HashSet<String> {
+        &self.options.abbreviations
+    }
+
+    /// Sets abbreviations (including the trailing period e. g. `Fig.` or `approx.`) after which a sentence must not end.
+    /// Sentence boundaries come from Unicode sentence segmentation, which does not have a built-in abbreviation list.
+    /// These abbreviations are applied on top of it: they can only remove boundaries, never add them.
+    /// Matching is case-sensitive. Replaces abbreviations set previously or in the [TokenizerOptions].
+    pub fn with_abbreviations(&mut self, abbrevs: HashSet<String>) {
+        self.options.abbreviations = abbrevs;
+    }
+
+    /// Generates the surface forms of `lemma` with the part-of-speech tag `pos_tag` by lookup in the tagger dictionary,
+    /// e. g. to inflect a replacement so it agrees with its context. Returns an empty vector if there is no such form.
+    pub fn synthesize(&self, lemma: &str, pos_tag: &str) -> Vec<String> {
+        self.tagger
+            .get_inflections(lemma, pos_tag)
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    /// Gets all part-of-speech tags the tagger of this tokenizer can assign.
+    /// Includes the special tags `SENT_START`, `SENT_END` and `UNKNOWN` as well as language-specific extra tags.
+    pub fn pos_tagset(&self) -> impl Iterator<Item = &str> {
+        self.tagger
+            .tag_store()
+            .left_values()
+            .map(|x| x.as_str())
+            .filter(|x| !x.is_empty())
+    }
+
     pub(crate) fn disambiguate_up_to_id<'t>(
         &'t self,
         mut tokens: Vec<IncompleteToken<'t>>,
         id: Option<&str>,
+        parallel: bool,
     ) -> Vec<IncompleteToken<'t>> {
         let n = id.map_or(self.rules.len(), |id| {
             self.rules.iter().position(|x| x.id == id).unwrap()
@@ -172,7 +387,7 @@ impl Tokenizer {
         while i < n {
             let finalized = finalize(tokens.clone());
             let result = self.rules[i..n]
-                .maybe_par_iter()
+                .maybe_par_iter_cond(parallel)
                 .enumerate()
                 .filter_map(|(j, rule)| {
                     let changes = rule.apply(&finalized, &self);
@@ -197,27 +412,172 @@ impl Tokenizer {
 
     /// Apply rule-based disambiguation to the tokens.
     /// This does not change the number of tokens, but can change the content arbitrarily.
+    ///
+    /// Disambiguation rules are sourced from the LanguageTool disambiguation rules of the language
+    /// (see [rules][Tokenizer::rules]) and adjust e. g. part-of-speech tags and lemmas. They must run before
+    /// grammatical error correction rules so that these see the disambiguated tokens:
+    ///
+    /// ```no_run
+    /// use nlprule::{Tokenizer, Rules, tokenizer::finalize};
+    ///
+    /// let tokenizer = Tokenizer::new("path/to/en_tokenizer.bin")?;
+    /// let rules = Rules::new("path/to/en_rules.bin")?;
+    ///
+    /// let tokens = finalize(tokenizer.disambiguate(tokenizer.tokenize("She was not been here.")));
+    /// let suggestions = rules.apply(&tokens, &tokenizer);
+    /// # Ok::<(), bincode::Error>(())
+    /// ```
     pub fn disambiguate<'t>(
         &'t self,
         tokens: Vec<IncompleteToken<'t>>,
     ) -> Vec<IncompleteToken<'t>> {
-        self.disambiguate_up_to_id(tokens, None)
+        self.disambiguate_up_to_id(tokens, None, true)
+    }
+
+    /// Splits the text into sentences and returns their byte spans. Uses Unicode sentence segmentation,
+    /// then merges sentences which were split after one of the configured abbreviations.
+    pub(crate) fn sentence_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        let mut ends_with_abbreviation = false;
+
+        for sentence in text.unicode_sentences() {
+            let start = sentence.as_ptr() as usize - text.as_ptr() as usize;
+            let end = start + sentence.len();
+
+            match spans.last_mut() {
+                Some(last) if ends_with_abbreviation => last.1 = end,
+                _ => spans.push((start, end)),
+            }
+
+            ends_with_abbreviation = sentence
+                .split_whitespace()
+                .last()
+                .is_some_and(|word| self.options.abbreviations.contains(word));
+        }
+
+        spans
+    }
+
+    /// Gets the char index of the start of each sentence of the text, see [sentence_spans][Tokenizer::sentence_spans].
+    pub(crate) fn sentence_starts(&self, text: &str) -> Vec<usize> {
+        sentence_char_starts(text, &self.sentence_spans(text))
     }
 
     /// Tokenize the given text. This applies chunking and tagging, but does not do disambiguation.
     pub fn tokenize<'t>(&'t self, text: &'t str) -> Vec<IncompleteToken<'t>> {
-        let sentence_indices = text
-            .unicode_sentences()
-            .map(|sentence| {
-                let ptr = sentence.as_ptr() as usize;
-                (ptr, ptr + sentence.len())
-            })
+        self.tokenize_sentences(text, &self.sentence_spans(text))
+    }
+
+    /// Tokenize the given text which is split into sentences with the given byte spans.
+    fn tokenize_sentences<'t>(
+        &'t self,
+        text: &'t str,
+        spans: &[(usize, usize)],
+    ) -> Vec<IncompleteToken<'t>> {
+        let ptr = text.as_ptr() as usize;
+        let sentence_indices = spans
+            .iter()
+            .map(|(start, end)| (ptr + start, ptr + end))
+            .fold((HashSet::new(), HashSet::new()), |mut a, x| {
+                a.0.insert(x.0);
+                a.1.insert(x.1);
+                a
+            });
+
+        self.tokenize_with_sentence_indices(text, sentence_indices)
+    }
+
+    /// Tokenize only the sentences of the text which overlap with the chars from `char_start` (inclusive)
+    /// to `char_end` (exclusive), e. g. to re-tokenize an edited region of a large document.
+    /// The spans of the returned tokens are relative to the whole text. Otherwise equivalent to [tokenize][Tokenizer::tokenize].
+    pub fn tokenize_subrange<'t>(
+        &'t self,
+        text: &'t str,
+        char_start: usize,
+        char_end: usize,
+    ) -> Vec<IncompleteToken<'t>> {
+        let byte_index = |char_idx| {
+            text.char_indices()
+                .nth(char_idx)
+                .map_or(text.len(), |(i, _)| i)
+        };
+        let (byte_start, byte_end) = (byte_index(char_start), byte_index(char_end));
+
+        let spans = self.sentence_spans(text);
+        let (start, end) = sentence_range(&spans, text, byte_start, byte_end);
+
+        let ptr = text.as_ptr() as usize;
+        let sentence_indices = spans
+            .iter()
+            .filter(|(x, y)| *x >= start && *y <= end)
+            .map(|(x, y)| (ptr + x, ptr + y))
             .fold((HashSet::new(), HashSet::new()), |mut a, x| {
                 a.0.insert(x.0);
                 a.1.insert(x.1);
                 a
             });
 
+        let char_offset = text[..start].chars().count();
+        let mut tokens = self.tokenize_with_sentence_indices(&text[start..end], sentence_indices);
+
+        for token in tokens.iter_mut() {
+            token.byte_span = (token.byte_span.0 + start, token.byte_span.1 + start);
+            token.char_span = (
+                token.char_span.0 + char_offset,
+                token.char_span.1 + char_offset,
+            );
+            token.has_space_before = text[..token.byte_span.0].ends_with(char::is_whitespace);
+            token.text = text;
+        }
+
+        // the last token is only a sentence end in the whole text if it ends exactly at a sentence boundary
+        if let Some(last) = tokens.last_mut() {
+            if end < text.len() {
+                last.is_sentence_end = spans.iter().any(|x| x.1 == last.byte_span.1);
+            }
+        }
+
+        tokens
+    }
+
+    /// Joins finalized tokens back into a string. Uses the text of each token and the whitespace which preceded
+    /// the token in the original text (see [whitespace_before][Token::whitespace_before]), so spacing is preserved
+    /// if tokens are removed or their text is changed. The special sentence start token is ignored.
+    pub fn detokenize(&self, tokens: &[Token]) -> String {
+        let mut output = String::new();
+
+        for token in tokens {
+            let text = token.word.text.as_ref();
+
+            if text.is_empty() {
+                continue;
+            }
+
+            output.push_str(token.whitespace_before());
+            output.push_str(text);
+        }
+
+        output
+    }
+
+    /// Tokenize the given text without splitting it into sentences i. e. the whole text is treated as exactly one sentence.
+    /// Otherwise equivalent to [tokenize][Tokenizer::tokenize].
+    pub fn tokenize_single_sentence<'t>(&'t self, text: &'t str) -> Vec<IncompleteToken<'t>> {
+        let ptr = text.as_ptr() as usize;
+
+        let sentence_indices = (
+            std::iter::once(ptr).collect(),
+            std::iter::once(ptr + text.len()).collect(),
+        );
+
+        self.tokenize_with_sentence_indices(text, sentence_indices)
+    }
+
+    fn tokenize_with_sentence_indices<'t>(
+        &'t self,
+        text: &'t str,
+        sentence_indices: (HashSet<usize>, HashSet<usize>),
+    ) -> Vec<IncompleteToken<'t>> {
         let mut current_char = 0;
         let token_strs = get_token_strs(text);
         let mut tokens: Vec<_> = token_strs
@@ -269,11 +629,13 @@ impl Tokenizer {
 
 #[cfg(test)]
 mod tests {
-    use super::Tokenizer;
+    use super::{finalize, sentence_span, Tokenized, Tokenizer};
+    use crate::utils::testing;
     use lazy_static::lazy_static;
     use quickcheck_macros::quickcheck;
     use std::fs::File;
     use std::io::BufReader;
+    use std::sync::Arc;
 
     #[quickcheck]
     fn can_tokenize_anything(text: String) -> bool {
@@ -287,4 +649,112 @@ mod tests {
         TOKENIZER.tokenize(&text);
         true
     }
+
+    #[test]
+    fn tokenized_can_be_reused() {
+        let tokenizer = testing::tokenizer(&[]);
+        let rules = crate::Rules::default();
+        let text = "A short text. Another sentence.";
+
+        let tokenized = Tokenized::new(text, &tokenizer);
+        let expected = finalize(tokenizer.disambiguate(tokenizer.tokenize(text)));
+
+        assert_eq!(tokenized.text(), text);
+        assert_eq!(
+            tokenized.iter().map(|x| x.byte_span).collect::<Vec<_>>(),
+            expected.iter().map(|x| x.byte_span).collect::<Vec<_>>()
+        );
+        for _ in 0..2 {
+            assert!(rules.apply(&tokenized, &tokenizer).is_empty());
+        }
+    }
+
+    #[test]
+    fn can_synthesize() {
+        let tokenizer = Tokenizer {
+            tagger: Arc::new(testing::tagger_from_dump(&[
+                "house\thouse\tNN",
+                "houses\thouse\tNNS",
+                "houses\thouse\tVBZ",
+                "mice\tmouse\tNNS",
+            ])),
+            ..Tokenizer::default()
+        };
+
+        assert_eq!(tokenizer.synthesize("house", "NNS"), vec!["houses"]);
+        assert_eq!(tokenizer.synthesize("house", "NN"), vec!["house"]);
+        assert_eq!(tokenizer.synthesize("mouse", "NNS"), vec!["mice"]);
+        assert!(tokenizer.synthesize("mouse", "NN").is_empty());
+        assert!(tokenizer.synthesize("cat", "NNS").is_empty());
+    }
+
+    #[test]
+    fn exposes_pos_candidates() {
+        let tokenizer = Tokenizer {
+            tagger: Arc::new(testing::tagger_from_dump(&[
+                "houses\thouse\tNNS",
+                "houses\thouse\tVBZ",
+            ])),
+            ..Tokenizer::default()
+        };
+
+        let tokens = tokenizer.tokenize("houses");
+        let mut candidates = tokens[0].word.pos_candidates();
+        candidates.sort_unstable();
+
+        assert_eq!(candidates, vec!["NNS", "VBZ"]);
+    }
+
+    #[test]
+    fn can_get_sentence_span() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let text = "  Ein Test.  ";
+        let tokens = finalize(tokenizer.tokenize(text));
+        assert_eq!(sentence_span(&tokens), (2, 11));
+        assert_eq!(sentence_span(&[]), (0, 0));
+    }
+
+    #[test]
+    fn can_tokenize_subrange() {
+        let tokenizer = testing::tokenizer(&[]);
+
+        let text = "Das ist ein Test. Zweiter Satz mit Ä! Dritter Satz.";
+        let all = tokenizer.tokenize(text);
+        let in_range = |start: usize, end: usize| {
+            all.iter()
+                .filter(|x| x.char_span.0 >= start && x.char_span.1 <= end)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(tokenizer.tokenize_subrange(text, 20, 22), in_range(18, 37));
+        assert_eq!(tokenizer.tokenize_subrange(text, 10, 40), in_range(0, 51));
+        assert_eq!(tokenizer.tokenize_subrange(text, 45, 45), in_range(38, 51));
+        assert_eq!(tokenizer.tokenize_subrange(text, 0, 51), all);
+    }
+
+    #[test]
+    fn abbreviations_do_not_end_sentences() {
+        let text = "See Fig. Three shows it. Done.";
+        let sentences = |tokenizer: &Tokenizer| {
+            tokenizer
+                .sentence_spans(text)
+                .into_iter()
+                .map(|(start, end)| &text[start..end])
+                .collect::<Vec<_>>()
+        };
+
+        let mut tokenizer = Tokenizer::default();
+        assert_eq!(
+            sentences(&tokenizer),
+            vec!["See Fig. ", "Three shows it. ", "Done."]
+        );
+
+        tokenizer.with_abbreviations(vec!["Fig.".to_string()].into_iter().collect());
+        assert_eq!(
+            sentences(&tokenizer),
+            vec!["See Fig. Three shows it. ", "Done."]
+        );
+    }
 }
@@ -270,6 +270,33 @@ impl Tagger {
         tags
     }
 
+    /// Get the inflected forms of the given lemma which have the given part-of-speech tag,
+    /// e. g. "houses" for the lemma "house" and the tag "NNS".
+    pub fn get_inflections(&self, lemma: &str, pos: &str) -> Vec<&str> {
+        let (lemma_id, tag_id) = match (
+            self.word_store.get_by_left(lemma),
+            self.tag_store.get_by_left(pos),
+        ) {
+            (Some(lemma_id), Some(tag_id)) => (lemma_id, tag_id),
+            _ => return Vec::new(),
+        };
+
+        self.groups
+            .get(lemma_id)
+            .map(|vec| {
+                vec.iter()
+                    .filter(|word_id| {
+                        self.tags
+                            .get(word_id)
+                            .and_then(|map| map.get(lemma_id))
+                            .is_some_and(|tag_ids| tag_ids.contains(tag_id))
+                    })
+                    .map(|x| self.word_store.get_by_right(x).unwrap().as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get the words with the same lemma as the given lemma.
     #[allow(clippy::clippy::ptr_arg)]
     pub fn get_group_members(&self, lemma: &String) -> Vec<&str> {
@@ -16,6 +16,7 @@ pub(crate) type DefaultHasher = hash_map::DefaultHasher;
 /// Owned versions of the types for use in longer-living structures not bound to the `'t` lifetime e.g. rule tests.
 pub mod owned {
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
 
     #[derive(Debug, Serialize, Deserialize, Hash, Eq, PartialEq)]
     pub struct WordId(pub String, pub Option<u32>);
@@ -73,6 +74,44 @@ pub mod owned {
         pub has_space_before: bool,
         pub chunks: Vec<String>,
     }
+
+    /// One group of a match i. e. the part of the text matched by one token of the pattern.
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct Group {
+        pub char_span: (usize, usize),
+        /// Whether the group matched. `false` for an optional part of the pattern which did not match any tokens.
+        #[serde(default)]
+        pub matched: bool,
+        /// The number of tokens in the group.
+        #[serde(default)]
+        pub n_tokens: usize,
+        pub text: String,
+    }
+
+    /// A match of a rule which is not bound to the lifetime of the tokens.
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct MatchGraph {
+        pub groups: Vec<Group>,
+        pub id_to_idx: HashMap<usize, usize>,
+    }
+
+    impl MatchGraph {
+        /// Gets the group at the given index. Index zero is the whole match.
+        pub fn by_index(&self, index: usize) -> &Group {
+            &self.groups[index]
+        }
+
+        /// Gets the group with the given ID as used in the rule (e. g. `\1` in a suggestion) if it exists.
+        pub fn by_id(&self, id: usize) -> Option<&Group> {
+            Some(&self.groups[*self.id_to_idx.get(&id)?])
+        }
+
+        /// Gets the number of tokens each part of the pattern consumed, in the order of the parts, i. e. the number
+        /// of tokens of every group except the whole match. Useful to debug greedy parts which consumed more tokens than expected.
+        pub fn consumption(&self) -> Vec<usize> {
+            self.groups.iter().skip(1).map(|x| x.n_tokens).collect()
+        }
+    }
 }
 
 /// A potentially identified word. If it is identified as a known word, many optimizations can be applied.
@@ -148,6 +187,22 @@ impl<'t> Word<'t> {
         Word { text, tags }
     }
 
+    /// Gets the distinct candidate part-of-speech tags of this word in the order they were assigned.
+    /// The tagger is dictionary-based so candidates are unranked. Before disambiguation these are all tags
+    /// the tagger considers possible, afterwards only the ones the disambiguation rules kept.
+    /// Includes special tags such as `SENT_END` but not the empty tag.
+    pub fn pos_candidates(&self) -> Vec<&'t str> {
+        let mut candidates: Vec<&'t str> = Vec::new();
+
+        for pos in self.tags.iter().map(|x| x.pos.0) {
+            if !pos.is_empty() && !candidates.contains(&pos) {
+                candidates.push(pos);
+            }
+        }
+
+        candidates
+    }
+
     pub fn to_owned_word(&self) -> owned::Word {
         owned::Word {
             text: self.text.to_owned_id(),
@@ -207,6 +262,19 @@ impl<'t> Token<'t> {
         }
     }
 
+    /// Gets the whitespace directly preceding this token in the text.
+    /// Useful to reconstruct text with its original spacing after changing tokens.
+    pub fn whitespace_before(&self) -> &'t str {
+        let before = &self.text[..self.byte_span.0];
+        &before[before.trim_end().len()..]
+    }
+
+    /// Whether the tagger recognized this token i. e. it was not given the special `UNKNOWN` part-of-speech tag
+    /// during [finalization][crate::tokenizer::finalize]. Disambiguation can not mark a token as unknown.
+    pub fn is_known(&self) -> bool {
+        !self.word.tags.iter().any(|x| x.pos.as_ref() == "UNKNOWN")
+    }
+
     pub fn to_owned_token(&self) -> owned::Token {
         owned::Token {
             word: self.word.to_owned_word(),
@@ -253,6 +321,30 @@ impl<'t> From<IncompleteToken<'t>> for Token<'t> {
     }
 }
 
+/// Converts a char index into `text` to a 1-based line and column. Lines are separated by `\n` or `\r\n`,
+/// columns are counted in chars. An index at the end of a line (i. e. at the line break) is in that line.
+pub fn line_col(text: &str, char_offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut col = 1;
+    let mut chars = text.chars().peekable();
+
+    for _ in 0..char_offset {
+        let c = match chars.next() {
+            Some(c) => c,
+            None => break,
+        };
+
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else if !(c == '\r' && chars.peek() == Some(&'\n')) {
+            col += 1;
+        }
+    }
+
+    (line, col)
+}
+
 /// Suggestion for change in a text.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Suggestion {
@@ -266,4 +358,133 @@ pub struct Suggestion {
     pub end: usize,
     /// The suggested replacement options for the text.
     pub replacements: Vec<String>,
+    /// The confidence of the rule this suggestion is from, see [confidence][crate::rule::Rule::confidence].
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// The index of the sentence this suggestion starts in (zero-based), according to the sentence segmentation of the tokenizer.
+    #[serde(default)]
+    pub sentence_index: usize,
+}
+
+impl Suggestion {
+    /// Gets the best replacement of this suggestion. Replacements are ordered by the rule from most to least
+    /// likely, and confidence is only known per rule, not per replacement, so this is always the first replacement.
+    /// Returns `None` if the suggestion has no replacements.
+    pub fn best(&self) -> Option<&str> {
+        self.replacements.first().map(|x| x.as_str())
+    }
+
+    /// Gets whether the first replacement only differs from the original text in `text` by capitalization
+    /// e. g. "monday" -> "Monday". `text` must be the text this suggestion was computed for.
+    pub fn changes_case(&self, text: &str) -> bool {
+        let original: String = text
+            .chars()
+            .skip(self.start)
+            .take(self.end - self.start)
+            .collect();
+
+        self.replacements.first().is_some_and(|replacement| {
+            *replacement != original && replacement.to_lowercase() == original.to_lowercase()
+        })
+    }
+
+    /// Gets the 1-based line and column of the start and end of this suggestion in `text`, e. g. to display it in an editor.
+    /// `text` must be the text this suggestion was computed for. See [line_col].
+    pub fn location(&self, text: &str) -> ((usize, usize), (usize, usize)) {
+        (line_col(text, self.start), line_col(text, self.end))
+    }
+
+    /// Checks whether this suggestion can be applied to `text` i. e. whether `start <= end <= text.chars().count()`
+    /// and there is at least one replacement. Useful for suggestions from an untrusted source such as a cache.
+    pub fn is_valid_for(&self, text: &str) -> bool {
+        self.start <= self.end && self.end <= text.chars().count() && !self.replacements.is_empty()
+    }
+}
+
+/// A view of a [Suggestion] with a stable serialized shape, intended as the response format of e. g. JSON APIs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SuggestionView {
+    /// The start character index of the text to replace.
+    pub offset: usize,
+    /// The length of the text to replace in characters.
+    pub length: usize,
+    /// The suggested replacement options for the text.
+    pub replacements: Vec<String>,
+    /// The ID of the rule this suggestion is from.
+    pub rule_id: String,
+    /// A human-readable message.
+    pub message: String,
+}
+
+impl From<&Suggestion> for SuggestionView {
+    fn from(suggestion: &Suggestion) -> Self {
+        SuggestionView {
+            offset: suggestion.start,
+            length: suggestion.end - suggestion.start,
+            replacements: suggestion.replacements.clone(),
+            rule_id: suggestion.source.clone(),
+            message: suggestion.message.clone(),
+        }
+    }
+}
+
+/// A token annotated with the rules matching it, e. g. to create datasets. See [annotate][crate::Rules::annotate].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TokenAnnotation {
+    /// The text of the token.
+    pub text: String,
+    /// The part-of-speech tags of the token after disambiguation.
+    pub pos: Vec<String>,
+    /// The start and end character index of the token.
+    pub char_span: (usize, usize),
+    /// The IDs of the rules with a match containing this token, ordered by priority.
+    pub rule_ids: Vec<String>,
+}
+
+/// The severity of a [Diagnostic], modeled after the severities of the Language Server Protocol.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    /// Gets the severity for a rule with the given [category type][crate::rule::Rule::category_type]:
+    /// misspellings are errors, style issues are information and everything else is a warning.
+    pub fn from_category_type(category_type: Option<&str>) -> Self {
+        match category_type {
+            Some("misspelling") => Severity::Error,
+            Some("style") | Some("register") => Severity::Information,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// A suggestion in a shape which maps directly to a diagnostic and code actions of the Language Server Protocol.
+/// See [diagnostics][crate::Rules::diagnostics].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// The start and end byte index of the text this diagnostic refers to.
+    pub byte_span: (usize, usize),
+    /// The severity of this diagnostic.
+    pub severity: Severity,
+    /// The ID of the rule this diagnostic is from.
+    pub rule_id: String,
+    /// A human-readable message.
+    pub message: String,
+    /// The suggested replacement options for the text.
+    pub replacements: Vec<String>,
+}
+
+/// An operation of a diff between a text and its corrected version, see [suggestions_to_diff][crate::rules::suggestions_to_diff].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum DiffOp {
+    /// Text which is unchanged.
+    Equal(String),
+    /// Text of the original which is removed.
+    Delete(String),
+    /// Text which is inserted in the corrected version.
+    Insert(String),
 }
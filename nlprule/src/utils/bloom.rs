@@ -0,0 +1,85 @@
+//! A Bloom filter of words which are compared case-insensitively. Used to cheaply check that a text does not
+//! contain any of a number of words without building a set of all its tokens.
+
+use crate::types::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use unicase::UniCase;
+
+const BITS_PER_WORD: usize = 8;
+const N_HASHES: u64 = 3;
+
+/// A probabilistic set of words. [might_contain][BloomFilter::might_contain] can return false positives,
+/// but never false negatives.
+#[derive(Debug)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Creates a filter containing the given words.
+    pub fn new<'a, I>(words: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let words = words.into_iter();
+        let n_bits = (words.len() * BITS_PER_WORD).max(64).next_power_of_two();
+
+        let mut filter = BloomFilter {
+            bits: vec![0; n_bits / 64],
+        };
+
+        for word in words {
+            for index in filter.indices(word) {
+                filter.bits[index / 64] |= 1 << (index % 64);
+            }
+        }
+
+        filter
+    }
+
+    fn indices(&self, word: &str) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::default();
+        UniCase::new(word).hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // double hashing: derive all hashes from the two halves of one 64-bit hash
+        let (a, b) = (hash & 0xFFFF_FFFF, (hash >> 32) | 1);
+        let mask = (self.bits.len() * 64 - 1) as u64;
+
+        (0..N_HASHES).map(move |i| (a.wrapping_add(i.wrapping_mul(b)) & mask) as usize)
+    }
+
+    /// Whether the filter might contain the word. If `false`, the word is certainly not in the filter.
+    pub fn might_contain(&self, word: &str) -> bool {
+        self.indices(word)
+            .all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_inserted_words_case_insensitively() {
+        let words = ["This", "is", "a", "test", "Straße"];
+        let filter = BloomFilter::new(words.iter().copied());
+
+        for word in &words {
+            assert!(filter.might_contain(word));
+            assert!(filter.might_contain(&word.to_uppercase()));
+        }
+
+        let absent = ["cat", "dog", "house", "tree", "walked", "blue", "yesterday"];
+        assert!(absent.iter().any(|x| !filter.might_contain(x)));
+    }
+
+    #[test]
+    fn empty_filter_contains_nothing() {
+        let filter = BloomFilter::new(std::iter::empty::<&str>());
+
+        assert!(!filter.might_contain("test"));
+        assert!(!filter.might_contain(""));
+    }
+}
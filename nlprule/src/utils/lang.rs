@@ -0,0 +1,516 @@
+//! Language-specific data for number words and dates, used by [NumberWordMatcher][crate::rule::engine::composition::NumberWordMatcher]
+//! and [DateAtom][crate::rule::engine::composition::concrete::DateAtom].
+//!
+//! The data of each language is only compiled in with the corresponding feature (`lang-en` or `lang-de`, both enabled by default).
+//! Without it, number words and month names of the language are never recognized.
+
+use serde::{Deserialize, Serialize};
+
+/// A language with data for number words and dates.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+/// A calendar date. The year is `None` if the date does not contain one e. g. for "March 3rd".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: Option<u32>,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Whether the day exists in the month. Without a year, the 29th of February is considered valid.
+    pub fn is_valid(&self) -> bool {
+        let days = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 => match self.year {
+                Some(year) if !(year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)) => 28,
+                _ => 29,
+            },
+            _ => return false,
+        };
+
+        self.day >= 1 && self.day <= days
+    }
+}
+
+impl Language {
+    /// Parses a number written in words e. g. "twenty-three" or "dreiundzwanzig". Case-insensitive.
+    /// Returns `None` if the text is not a number in this language or the data of the language is not compiled in.
+    #[cfg_attr(
+        not(any(feature = "lang-en", feature = "lang-de")),
+        allow(unused_variables)
+    )]
+    pub fn parse_number_words(self, text: &str) -> Option<u64> {
+        match self {
+            #[cfg(feature = "lang-en")]
+            Language::English => en::parse_number_words(&text.to_lowercase()),
+            #[cfg(feature = "lang-de")]
+            Language::German => de::parse_number_words(&text.to_lowercase()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Gets the month (starting at one for January) for a month name. Case-insensitive.
+    pub fn month(self, text: &str) -> Option<u32> {
+        let text = text.to_lowercase();
+
+        let months: &[&str] = match self {
+            #[cfg(feature = "lang-en")]
+            Language::English => &en::MONTHS,
+            #[cfg(feature = "lang-de")]
+            Language::German => &de::MONTHS,
+            #[allow(unreachable_patterns)]
+            _ => &[],
+        };
+
+        months.iter().position(|x| *x == text).map(|x| x as u32 + 1)
+    }
+
+    /// Parses a date at the start of `words` (the texts of consecutive tokens) and returns it
+    /// together with the number of words it spans. Recognized formats are
+    /// - a single word in ISO format e. g. "2021-02-28" in any language,
+    /// - English: "28th of February", "28 February", "February 28th" with an optional year (e. g. "February 28, 2021") or "02/28/2021",
+    /// - German: "28. Februar" with an optional year or "28.02.2021".
+    ///
+    /// The day must be between 1 and 31 and the month between 1 and 12 to be recognized, but the date
+    /// does not have to exist (see [is_valid][Date::is_valid]) so e. g. "31st of February" is recognized.
+    pub fn parse_date(self, words: &[&str]) -> Option<(Date, usize)> {
+        if let Some(date) = words.first().and_then(|x| parse_numeric(x, '-', [0, 1, 2])) {
+            return Some((date, 1));
+        }
+
+        match self {
+            Language::English => {
+                if let Some(date) = words.first().and_then(|x| parse_numeric(x, '/', [2, 0, 1])) {
+                    return Some((date, 1));
+                }
+
+                // "28th of February" or "28 February"
+                if let Some(day) = words
+                    .first()
+                    .and_then(|x| parse_day(x, &["st", "nd", "rd", "th"]))
+                {
+                    let offset = if words.get(1).is_some_and(|x| x.eq_ignore_ascii_case("of")) {
+                        2
+                    } else {
+                        1
+                    };
+
+                    if let Some(month) = words.get(offset).and_then(|x| self.month(x)) {
+                        return Some(with_year(day, month, words, offset + 1));
+                    }
+                }
+
+                // "February 28th"
+                let month = words.first().and_then(|x| self.month(x))?;
+                let day = words
+                    .get(1)
+                    .and_then(|x| parse_day(x, &["st", "nd", "rd", "th"]))?;
+                Some(with_year(day, month, words, 2))
+            }
+            Language::German => {
+                if let Some(date) = words.first().and_then(|x| parse_numeric(x, '.', [2, 1, 0])) {
+                    return Some((date, 1));
+                }
+
+                // "28. Februar" where the dot is either part of the day or a separate token
+                let (day, offset) = match words.first().and_then(|x| parse_day(x, &["."])) {
+                    Some(day) if words[0].ends_with('.') => (day, 1),
+                    Some(day) if words.get(1) == Some(&".") => (day, 2),
+                    _ => return None,
+                };
+
+                let month = words.get(offset).and_then(|x| self.month(x))?;
+                Some(with_year(day, month, words, offset + 1))
+            }
+        }
+    }
+}
+
+/// Parses a day of the month with an optional suffix e. g. "28th".
+fn parse_day(word: &str, suffixes: &[&str]) -> Option<u32> {
+    let lower = word.to_lowercase();
+    let digits = suffixes
+        .iter()
+        .find_map(|x| lower.strip_suffix(x))
+        .unwrap_or(&lower);
+
+    if digits.is_empty() || digits.len() > 2 || !digits.chars().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+
+    digits.parse().ok().filter(|x| (1..=31).contains(x))
+}
+
+/// Parses a four digit year.
+fn parse_year(word: &str) -> Option<u32> {
+    if word.len() == 4 && word.chars().all(|x| x.is_ascii_digit()) {
+        word.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Creates a date from day and month, using the year after `offset` if there is one (optionally preceded by a comma).
+fn with_year(day: u32, month: u32, words: &[&str], offset: usize) -> (Date, usize) {
+    let offset_year = if words.get(offset) == Some(&",") {
+        offset + 1
+    } else {
+        offset
+    };
+
+    match words.get(offset_year).and_then(|x| parse_year(x)) {
+        Some(year) => (
+            Date {
+                year: Some(year),
+                month,
+                day,
+            },
+            offset_year + 1,
+        ),
+        None => (
+            Date {
+                year: None,
+                month,
+                day,
+            },
+            offset,
+        ),
+    }
+}
+
+/// Parses a date consisting of three numbers separated by `separator`. `order` is the index of the year, month and day.
+fn parse_numeric(word: &str, separator: char, order: [usize; 3]) -> Option<Date> {
+    let parts: Vec<_> = word.split(separator).collect();
+    if parts.len() != 3
+        || parts
+            .iter()
+            .any(|x| x.is_empty() || !x.chars().all(|x| x.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let year = parse_year(parts[order[0]])?;
+    let month = parts[order[1]]
+        .parse()
+        .ok()
+        .filter(|x| (1..=12).contains(x))?;
+    let day = parse_day(parts[order[2]], &[])?;
+
+    Some(Date {
+        year: Some(year),
+        month,
+        day,
+    })
+}
+
+#[cfg(feature = "lang-en")]
+mod en {
+    const UNITS: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+
+    const SCALES: [(&str, u64); 3] = [
+        ("billion", 1_000_000_000),
+        ("million", 1_000_000),
+        ("thousand", 1_000),
+    ];
+
+    pub const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+
+    fn unit(word: &str) -> Option<u64> {
+        UNITS.iter().position(|x| *x == word).map(|x| x as u64)
+    }
+
+    fn tens(word: &str) -> Option<u64> {
+        TENS.iter()
+            .position(|x| !x.is_empty() && *x == word)
+            .map(|x| x as u64 * 10)
+    }
+
+    /// Parses e. g. "three hundred and twenty one".
+    fn below_thousand(words: &[&str]) -> Option<u64> {
+        let (hundreds, rest) = match words {
+            [first, "hundred", rest @ ..] => {
+                let hundreds = unit(first).filter(|x| (1..=9).contains(x))?;
+                let rest = match rest {
+                    ["and", rest @ ..] if !rest.is_empty() => rest,
+                    rest => rest,
+                };
+                (hundreds * 100, rest)
+            }
+            rest => (0, rest),
+        };
+
+        let rest = match rest {
+            [] if hundreds > 0 => 0,
+            [word] => unit(word)
+                .filter(|x| hundreds == 0 || *x > 0)
+                .or_else(|| tens(word))?,
+            [ten, one] => tens(ten)? + unit(one).filter(|x| (1..=9).contains(x))?,
+            _ => return None,
+        };
+
+        Some(hundreds + rest)
+    }
+
+    fn parse_scaled(words: &[&str], scales: &[(&str, u64)]) -> Option<u64> {
+        let ((name, value), smaller) = match scales.split_first() {
+            Some(scale) => scale,
+            None => return below_thousand(words),
+        };
+
+        match words.iter().position(|x| x == name) {
+            Some(i) => {
+                let count = below_thousand(&words[..i]).filter(|x| *x > 0)?;
+                let rest = match &words[i + 1..] {
+                    [] => 0,
+                    ["and", rest @ ..] | rest => parse_scaled(rest, smaller)?,
+                };
+
+                Some(count * value + rest)
+            }
+            None => parse_scaled(words, smaller),
+        }
+    }
+
+    pub fn parse_number_words(text: &str) -> Option<u64> {
+        let words: Vec<_> = text
+            .split(|x: char| x == '-' || x.is_whitespace())
+            .filter(|x| !x.is_empty())
+            .collect();
+
+        parse_scaled(&words, &SCALES)
+    }
+}
+
+#[cfg(feature = "lang-de")]
+mod de {
+    const UNITS: [&str; 20] = [
+        "null",
+        "eins",
+        "zwei",
+        "drei",
+        "vier",
+        "fünf",
+        "sechs",
+        "sieben",
+        "acht",
+        "neun",
+        "zehn",
+        "elf",
+        "zwölf",
+        "dreizehn",
+        "vierzehn",
+        "fünfzehn",
+        "sechzehn",
+        "siebzehn",
+        "achtzehn",
+        "neunzehn",
+    ];
+
+    const TENS: [&str; 10] = [
+        "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig",
+        "neunzig",
+    ];
+
+    pub const MONTHS: [&str; 12] = [
+        "januar",
+        "februar",
+        "märz",
+        "april",
+        "mai",
+        "juni",
+        "juli",
+        "august",
+        "september",
+        "oktober",
+        "november",
+        "dezember",
+    ];
+
+    /// A unit as the first part of a compound, where one is "ein" instead of "eins".
+    fn prefix_unit(word: &str) -> Option<u64> {
+        if word == "ein" {
+            Some(1)
+        } else {
+            UNITS[2..=9]
+                .iter()
+                .position(|x| *x == word)
+                .map(|x| x as u64 + 2)
+        }
+    }
+
+    fn below_hundred(word: &str) -> Option<u64> {
+        if let Some(x) = UNITS.iter().position(|x| *x == word) {
+            return Some(x as u64);
+        }
+        if let Some(x) = TENS.iter().position(|x| !x.is_empty() && *x == word) {
+            return Some(x as u64 * 10);
+        }
+
+        // e. g. "dreiundzwanzig"
+        let (one, ten) = word.split_once("und")?;
+        let ten = TENS.iter().position(|x| !x.is_empty() && *x == ten)?;
+        Some(prefix_unit(one)? + ten as u64 * 10)
+    }
+
+    fn below_thousand(word: &str) -> Option<u64> {
+        // split at "hundert" first since it contains "und"
+        match word.split_once("hundert") {
+            Some((hundreds, rest)) => {
+                let hundreds = if hundreds.is_empty() {
+                    1
+                } else {
+                    prefix_unit(hundreds)?
+                };
+                let rest = if rest.is_empty() {
+                    0
+                } else {
+                    below_hundred(rest).filter(|x| *x > 0)?
+                };
+
+                Some(hundreds * 100 + rest)
+            }
+            None => below_hundred(word),
+        }
+    }
+
+    /// Parses a number below one million written as one word e. g. "zweitausenddreihunderteins".
+    pub fn parse_number_words(text: &str) -> Option<u64> {
+        match text.split_once("tausend") {
+            Some((thousands, rest)) => {
+                let thousands = match thousands {
+                    "" | "ein" => 1,
+                    thousands => below_thousand(thousands).filter(|x| *x > 0)?,
+                };
+                let rest = if rest.is_empty() {
+                    0
+                } else {
+                    below_thousand(rest).filter(|x| *x > 0)?
+                };
+
+                Some(thousands * 1000 + rest)
+            }
+            None => below_thousand(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "lang-en")]
+    fn parses_english_number_words() {
+        let parse = |x| Language::English.parse_number_words(x);
+
+        assert_eq!(parse("seven"), Some(7));
+        assert_eq!(parse("Twenty-Three"), Some(23));
+        assert_eq!(parse("one hundred and five"), Some(105));
+        assert_eq!(parse("three thousand two hundred"), Some(3200));
+        assert_eq!(parse("two million and one"), Some(2_000_001));
+        assert_eq!(parse("five twenty"), None);
+        assert_eq!(parse("hundred"), None);
+        assert_eq!(parse("twenty-eleven"), None);
+        assert_eq!(parse("cat"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-de")]
+    fn parses_german_number_words() {
+        let parse = |x| Language::German.parse_number_words(x);
+
+        assert_eq!(parse("sieben"), Some(7));
+        assert_eq!(parse("Dreiundzwanzig"), Some(23));
+        assert_eq!(parse("einhundertfünf"), Some(105));
+        assert_eq!(parse("hunderteins"), Some(101));
+        assert_eq!(parse("zweitausenddreihundert"), Some(2300));
+        assert_eq!(parse("einsundzwanzig"), None);
+        assert_eq!(parse("hund"), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "lang-en", feature = "lang-de"))]
+    fn parses_dates() {
+        let en = |x: &[&str]| Language::English.parse_date(x);
+        let de = |x: &[&str]| Language::German.parse_date(x);
+        let date = |year, month, day| Date { year, month, day };
+
+        assert_eq!(
+            en(&["31st", "of", "February", "is"]),
+            Some((date(None, 2, 31), 3))
+        );
+        assert_eq!(
+            en(&["February", "28", ",", "2021"]),
+            Some((date(Some(2021), 2, 28), 4))
+        );
+        assert_eq!(en(&["02/29/2020"]), Some((date(Some(2020), 2, 29), 1)));
+        assert_eq!(en(&["2021-04-31"]), Some((date(Some(2021), 4, 31), 1)));
+        assert_eq!(en(&["32nd", "of", "May"]), None);
+        assert_eq!(en(&["May", "I"]), None);
+
+        assert_eq!(de(&["31", ".", "April"]), Some((date(None, 4, 31), 3)));
+        assert_eq!(
+            de(&["29.", "Februar", "2021"]),
+            Some((date(Some(2021), 2, 29), 3))
+        );
+        assert_eq!(de(&["28.02.2021"]), Some((date(Some(2021), 2, 28), 1)));
+        assert_eq!(de(&["3", "Äpfel"]), None);
+    }
+
+    #[test]
+    fn validates_dates() {
+        let date = |year, month, day| Date { year, month, day };
+
+        assert!(date(None, 2, 29).is_valid());
+        assert!(date(Some(2000), 2, 29).is_valid());
+        assert!(!date(Some(1900), 2, 29).is_valid());
+        assert!(!date(Some(2021), 2, 29).is_valid());
+        assert!(!date(None, 4, 31).is_valid());
+        assert!(date(None, 12, 31).is_valid());
+    }
+}
@@ -1,8 +1,15 @@
-use lazy_static::lazy_static;
-use onig::{Captures, Regex};
+#[cfg(feature = "regex")]
+use onig::Captures;
+#[cfg(all(feature = "regex-pure", not(feature = "regex")))]
+use regex_crate::Captures;
 
+pub mod bloom;
+pub mod lang;
 pub mod parallelism;
 pub mod regex;
+pub mod sorted;
+#[cfg(any(test, feature = "bench"))]
+pub mod testing;
 
 // see https://stackoverflow.com/questions/38406793/why-is-capitalizing-the-first-letter-of-a-string-so-convoluted-in-rust
 pub fn apply_to_first<F>(string: &str, func: F) -> String
@@ -26,7 +33,24 @@ pub fn is_uppercase(string: &str) -> bool {
     !string.chars().any(|x| x.is_lowercase())
 }
 
+/// Applies the capitalization pattern of `original` to `replacement`. Uppercase and title case are transferred,
+/// anything else leaves `replacement` unchanged. If the two only differ by case, `replacement` is returned as-is.
+pub fn transfer_case(original: &str, replacement: &str) -> String {
+    let has_letter = |x: &str| x.chars().any(|c| c.is_alphabetic());
+
+    if !has_letter(original) || original.to_lowercase() == replacement.to_lowercase() {
+        replacement.to_string()
+    } else if original.chars().filter(|c| c.is_alphabetic()).count() > 1 && is_uppercase(original) {
+        replacement.to_uppercase()
+    } else if original.chars().next().is_some_and(|c| c.is_uppercase()) {
+        apply_to_first(replacement, |c| c.to_uppercase().collect())
+    } else {
+        replacement.to_string()
+    }
+}
+
 // see https://github.com/rust-onig/rust-onig/issues/59#issuecomment-340160520
+#[cfg(feature = "regex")]
 pub fn dollar_replace(mut replacement: String, caps: &Captures) -> String {
     for i in 1..caps.len() {
         replacement = replacement.replace(&format!("${}", i), caps.at(i).unwrap_or(""));
@@ -34,13 +58,27 @@ pub fn dollar_replace(mut replacement: String, caps: &Captures) -> String {
     replacement
 }
 
+#[cfg(all(feature = "regex-pure", not(feature = "regex")))]
+pub fn dollar_replace(mut replacement: String, caps: &Captures) -> String {
+    for i in 1..caps.len() {
+        replacement =
+            replacement.replace(&format!("${}", i), caps.get(i).map_or("", |x| x.as_str()));
+    }
+    replacement
+}
+
 // remove duplicate whitespaces
 pub fn normalize_whitespace(string: &str) -> String {
-    lazy_static! {
-        static ref REGEX: Regex = Regex::new(r"(\s)\s+").unwrap();
-    }
+    let mut prev_is_whitespace = false;
 
-    REGEX.replace_all(string, |caps: &Captures| caps.at(1).unwrap().to_string())
+    string
+        .chars()
+        .filter(|c| {
+            let keep = !(prev_is_whitespace && c.is_whitespace());
+            prev_is_whitespace = c.is_whitespace();
+            keep
+        })
+        .collect()
 }
 
 #[inline]
@@ -1,9 +1,24 @@
+//! A serializable regex. Regexes need the `regex` feature (on by default) which depends on Oniguruma,
+//! or the `regex-pure` feature which uses the pure-Rust [regex](https://docs.rs/regex) crate instead e. g. to cross-compile
+//! to musl or WebAssembly. If both are enabled, Oniguruma is used. Without either, loading anything containing a regex fails with an error.
+//!
+//! The rules of LanguageTool are written for Java regexes, which Oniguruma supports with its Java syntax. The `regex` crate
+//! does not support lookaround (`(?=...)`, `(?!...)`, `(?<=...)`, `(?<!...)`), backreferences (`\1`), possessive quantifiers
+//! and atomic groups, and its `\w`, `\d` and `\b` are Unicode-aware by default. Loading rules or a tokenizer with such a regex
+//! fails with the `regex-pure` feature, so these have to be ported or removed before building the binary.
+//! Regexes are compiled case-insensitively in the same way with both backends.
+
 use crate::Error;
+#[cfg(feature = "regex")]
 use onig::{Regex, RegexOptions};
+#[cfg(all(feature = "regex-pure", not(feature = "regex")))]
+use regex_crate::{Regex, RegexBuilder};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::hash::{Hash, Hasher};
-use std::ops::Deref;
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
+use std::{ops::Deref, sync::Arc};
 
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
 fn unescape<S: AsRef<str>>(string: S, c: &str) -> String {
     let placeholder = "###escaped_backslash###";
 
@@ -24,8 +39,9 @@ struct RegexFields {
 pub struct SerializeRegex {
     regex_str: String,
     case_sensitive: bool,
+    #[cfg(any(feature = "regex", feature = "regex-pure"))]
     #[serde(skip_serializing)]
-    regex: Regex,
+    regex: Arc<Regex>,
 }
 
 impl Hash for SerializeRegex {
@@ -35,38 +51,92 @@ impl Hash for SerializeRegex {
     }
 }
 
+#[cfg(not(any(feature = "regex", feature = "regex-pure")))]
 impl<'de> Deserialize<'de> for SerializeRegex {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         let fields: RegexFields = Deserialize::deserialize(deserializer)?;
-        Ok(SerializeRegex {
-            regex: SerializeRegex::compile(&fields.regex_str, fields.case_sensitive).unwrap(),
-            regex_str: fields.regex_str,
-            case_sensitive: fields.case_sensitive,
-        })
+        Err(serde::de::Error::custom(SerializeRegex::unsupported(
+            &fields.regex_str,
+        )))
     }
 }
 
+/// Stub used if the `regex` and `regex-pure` features are disabled. Can never be constructed, so the methods are unreachable.
+#[cfg(not(any(feature = "regex", feature = "regex-pure")))]
 impl SerializeRegex {
-    fn compile(regex_str: &str, case_sensitive: bool) -> Result<Regex, onig::Error> {
-        Regex::with_options(
-            regex_str,
-            if case_sensitive {
-                RegexOptions::REGEX_OPTION_NONE
-            } else {
-                RegexOptions::REGEX_OPTION_IGNORECASE
-            },
-            onig::Syntax::java(),
+    fn unsupported(regex_str: &str) -> String {
+        format!(
+            "regex `{}` can not be used: nlprule was built without the `regex` or `regex-pure` feature.",
+            regex_str
         )
     }
 
     pub fn new(
+        regex_str: &str,
+        _must_fully_match: bool,
+        _case_sensitive: bool,
+    ) -> Result<Self, Error> {
+        Err(Error::Unimplemented(SerializeRegex::unsupported(regex_str)))
+    }
+
+    pub fn is_match(&self, _text: &str) -> bool {
+        unreachable!()
+    }
+
+    pub fn group_at<'a>(&self, _text: &'a str, _index: usize) -> Option<&'a str> {
+        unreachable!()
+    }
+
+    pub fn captures<'a>(&self, _text: &'a str) -> Option<Vec<Option<&'a str>>> {
+        unreachable!()
+    }
+
+    pub fn captures_pos<'a>(
+        &'a self,
+        _text: &'a str,
+    ) -> impl Iterator<Item = Vec<Option<(usize, usize)>>> + 'a {
+        std::iter::empty()
+    }
+
+    pub fn replace_all_dollar(&self, _text: &str, _replacement: &str) -> String {
+        unreachable!()
+    }
+}
+
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
+impl<'de> Deserialize<'de> for SerializeRegex {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields: RegexFields = Deserialize::deserialize(deserializer)?;
+        Ok(SerializeRegex {
+            regex: Arc::new(
+                SerializeRegex::compile(&fields.regex_str, fields.case_sensitive).map_err(|x| {
+                    serde::de::Error::custom(format!(
+                        "regex `{}` can not be compiled: {}",
+                        fields.regex_str, x
+                    ))
+                })?,
+            ),
+            regex_str: fields.regex_str,
+            case_sensitive: fields.case_sensitive,
+        })
+    }
+}
+
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
+impl SerializeRegex {
+    /// Converts a Java regex string to the equivalent pattern string for Oniguruma.
+    /// Returns the pattern and whether it is case sensitive.
+    pub(crate) fn fix(
         regex_str: &str,
         must_fully_match: bool,
         case_sensitive: bool,
-    ) -> Result<Self, Error> {
+    ) -> (String, bool) {
         // TODO: more exhaustive backslash check
         let mut fixed = unescape(unescape(unescape(regex_str, "!"), ","), "/");
         let mut case_sensitive = case_sensitive;
@@ -95,19 +165,140 @@ impl SerializeRegex {
             fixed
         };
 
+        (fixed, case_sensitive)
+    }
+
+    /// Creates a regex from an already fixed pattern (see [SerializeRegex::fix]), reusing the compiled regex if one is given.
+    pub(crate) fn from_fixed(
+        fixed: String,
+        case_sensitive: bool,
+        compiled: Option<Arc<Regex>>,
+    ) -> Result<Self, Error> {
+        let regex = match compiled {
+            Some(regex) => regex,
+            None => Arc::new(
+                SerializeRegex::compile(&fixed, case_sensitive)
+                    .map_err(|x| Error::Regex(format!("{}", x)))?,
+            ),
+        };
+
         Ok(SerializeRegex {
-            regex: SerializeRegex::compile(&fixed, case_sensitive)
-                .map_err(|x| Error::Unexpected(format!("{}", x)))?,
+            regex,
             regex_str: fixed,
             case_sensitive,
         })
     }
+
+    /// Gets the compiled regex. Can be shared between regexes with the same pattern.
+    #[cfg(feature = "compile")]
+    pub(crate) fn compiled(&self) -> &Arc<Regex> {
+        &self.regex
+    }
+
+    pub fn new(
+        regex_str: &str,
+        must_fully_match: bool,
+        case_sensitive: bool,
+    ) -> Result<Self, Error> {
+        let (fixed, case_sensitive) =
+            SerializeRegex::fix(regex_str, must_fully_match, case_sensitive);
+
+        SerializeRegex::from_fixed(fixed, case_sensitive, None)
+    }
 }
 
+#[cfg(any(feature = "regex", feature = "regex-pure"))]
 impl Deref for SerializeRegex {
     type Target = Regex;
 
     fn deref(&self) -> &Self::Target {
-        &self.regex
+        self.regex.as_ref()
+    }
+}
+
+#[cfg(feature = "regex")]
+impl SerializeRegex {
+    fn compile(regex_str: &str, case_sensitive: bool) -> Result<Regex, onig::Error> {
+        Regex::with_options(
+            regex_str,
+            if case_sensitive {
+                RegexOptions::REGEX_OPTION_NONE
+            } else {
+                RegexOptions::REGEX_OPTION_IGNORECASE
+            },
+            onig::Syntax::java(),
+        )
+    }
+
+    /// Gets the text of the capture group at `index` of the first match in `text` if there is one.
+    pub fn group_at<'a>(&self, text: &'a str, index: usize) -> Option<&'a str> {
+        self.captures(text)?.get(index).copied().flatten()
+    }
+
+    /// Gets the text of all capture groups (including group zero i. e. the whole match) of the first match in `text`.
+    /// Groups which did not participate in the match are `None`.
+    pub fn captures<'a>(&self, text: &'a str) -> Option<Vec<Option<&'a str>>> {
+        let captures = self.regex.captures(text)?;
+        Some((0..captures.len()).map(|i| captures.at(i)).collect())
+    }
+
+    /// Gets the byte positions of all capture groups (including group zero i. e. the whole match) for each match in `text`.
+    pub fn captures_pos<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> impl Iterator<Item = Vec<Option<(usize, usize)>>> + 'a {
+        self.regex
+            .captures_iter(text)
+            .map(|captures| captures.iter_pos().collect())
+    }
+
+    /// Replaces all matches in `text` with `replacement`. `$1`, `$2` etc. in the replacement refer to capture groups.
+    pub fn replace_all_dollar(&self, text: &str, replacement: &str) -> String {
+        self.regex.replace_all(text, |caps: &onig::Captures| {
+            super::dollar_replace(replacement.to_string(), caps)
+        })
+    }
+}
+
+#[cfg(all(feature = "regex-pure", not(feature = "regex")))]
+impl SerializeRegex {
+    fn compile(regex_str: &str, case_sensitive: bool) -> Result<Regex, regex_crate::Error> {
+        RegexBuilder::new(regex_str)
+            .case_insensitive(!case_sensitive)
+            .build()
+    }
+
+    /// Gets the text of the capture group at `index` of the first match in `text` if there is one.
+    pub fn group_at<'a>(&self, text: &'a str, index: usize) -> Option<&'a str> {
+        self.captures(text)?.get(index).copied().flatten()
+    }
+
+    /// Gets the text of all capture groups (including group zero i. e. the whole match) of the first match in `text`.
+    /// Groups which did not participate in the match are `None`.
+    pub fn captures<'a>(&self, text: &'a str) -> Option<Vec<Option<&'a str>>> {
+        let captures = self.regex.captures(text)?;
+        Some(captures.iter().map(|x| x.map(|x| x.as_str())).collect())
+    }
+
+    /// Gets the byte positions of all capture groups (including group zero i. e. the whole match) for each match in `text`.
+    pub fn captures_pos<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> impl Iterator<Item = Vec<Option<(usize, usize)>>> + 'a {
+        self.regex.captures_iter(text).map(|captures| {
+            captures
+                .iter()
+                .map(|x| x.map(|x| (x.start(), x.end())))
+                .collect()
+        })
+    }
+
+    /// Replaces all matches in `text` with `replacement`. `$1`, `$2` etc. in the replacement refer to capture groups.
+    pub fn replace_all_dollar(&self, text: &str, replacement: &str) -> String {
+        self.regex
+            .replace_all(text, |caps: &regex_crate::Captures| {
+                super::dollar_replace(replacement.to_string(), caps)
+            })
+            .into_owned()
     }
 }
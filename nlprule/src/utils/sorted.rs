@@ -0,0 +1,86 @@
+//! Serialization of hash maps and sets in sorted order, for use with `#[serde(serialize_with = "...")]`.
+//! The iteration order of hash maps and sets is random, so serializing them directly makes the output differ between runs.
+//! Sorted sequences have the same serialized format, so fields can be deserialized as usual.
+
+use serde::{Serialize, Serializer};
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+};
+
+/// Serializes a set (or a pointer to a set, e. g. an `Arc`) in sorted order.
+pub fn set<C, T, S>(set: &C, serializer: S) -> Result<S::Ok, S::Error>
+where
+    C: Borrow<HashSet<T>>,
+    T: Ord + Serialize,
+    S: Serializer,
+{
+    set.borrow()
+        .iter()
+        .collect::<BTreeSet<_>>()
+        .serialize(serializer)
+}
+
+/// Serializes an optional set in sorted order.
+pub fn option_set<T, S>(set: &Option<HashSet<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Ord + Serialize,
+    S: Serializer,
+{
+    set.as_ref()
+        .map(|x| x.iter().collect::<BTreeSet<_>>())
+        .serialize(serializer)
+}
+
+/// Serializes a map in order of its keys.
+pub fn map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    };
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Sets {
+        #[serde(serialize_with = "super::set")]
+        set: HashSet<String>,
+        #[serde(serialize_with = "super::set")]
+        shared: Arc<HashSet<u32>>,
+        #[serde(serialize_with = "super::option_set")]
+        option: Option<HashSet<u32>>,
+        #[serde(serialize_with = "super::map")]
+        map: HashMap<usize, usize>,
+    }
+
+    fn sets() -> Sets {
+        // every set has its own random hasher state, so the iteration order differs between instances
+        Sets {
+            set: (0..100).map(|x| x.to_string()).collect(),
+            shared: Arc::new((0..100).collect()),
+            option: Some((0..100).collect()),
+            map: (0..100).map(|x| (x, x * 2)).collect(),
+        }
+    }
+
+    #[test]
+    fn serializes_deterministically() {
+        let bytes = bincode::serialize(&sets()).unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(bincode::serialize(&sets()).unwrap(), bytes);
+        }
+
+        let deserialized: Sets = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(deserialized, sets());
+    }
+}
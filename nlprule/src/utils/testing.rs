@@ -0,0 +1,229 @@
+//! Fixtures shared by the unit tests and the benchmarks.
+
+use crate::{
+    rule::{
+        engine::{
+            composition::{
+                concrete, Atom, Composition, Matcher, NotAtom, OrAtom, Part, Quantifier,
+                SetMatcher, TextMatcher,
+            },
+            Engine, TokenEngine,
+        },
+        grammar::{Synthesizer, SynthesizerPart},
+        Rule,
+    },
+    tokenizer::{tag::Tagger, Tokenizer},
+    types::*,
+};
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A tagger without a dictionary which knows the special tags (e. g. `SENT_END`) and `extra_tags`.
+pub fn tagger(extra_tags: &[&str]) -> Tagger {
+    Tagger::from_dumps(
+        &[] as &[&str],
+        &[] as &[&str],
+        extra_tags,
+        &Default::default(),
+    )
+    .unwrap()
+}
+
+/// A tagger with a dictionary of `lines` in the format of tag dumps i. e. `word\tlemma\ttag`.
+/// The dump is written to a temporary file which is unique per call and removed afterwards.
+pub fn tagger_from_dump(lines: &[&str]) -> Tagger {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "nlprule_test_dump_{}_{}.txt",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+
+    let mut file = std::fs::File::create(&path).unwrap();
+    for line in lines {
+        writeln!(file, "{}", line).unwrap();
+    }
+    drop(file);
+
+    let tagger = Tagger::from_dumps(
+        &[path.to_str().unwrap()],
+        &[] as &[&str],
+        &[] as &[&str],
+        &Default::default(),
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    tagger.unwrap()
+}
+
+/// A tokenizer without disambiguation rules and chunker which uses [tagger] with `extra_tags`.
+pub fn tokenizer(extra_tags: &[&str]) -> Tokenizer {
+    Tokenizer {
+        tagger: Arc::new(tagger(extra_tags)),
+        ..Tokenizer::default()
+    }
+}
+
+/// Splits the text at single spaces into tokens without part-of-speech tags.
+pub fn tokenize<'t>(text: &'t str, tagger: &'t Tagger) -> Vec<Token<'t>> {
+    let mut tokens = Vec::new();
+    let mut char_start = 0;
+    let mut byte_start = 0;
+
+    for word in text.split(' ') {
+        let n_chars = word.chars().count();
+
+        tokens.push(Token {
+            word: Word::new_with_tags(WordId(word.into(), None), Vec::new()),
+            char_span: (char_start, char_start + n_chars),
+            byte_span: (byte_start, byte_start + word.len()),
+            has_space_before: char_start > 0,
+            chunks: Vec::new(),
+            text,
+            tagger,
+        });
+
+        char_start += n_chars + 1;
+        byte_start += word.len() + 1;
+    }
+
+    tokens
+}
+
+/// An atom matching the token text `text` case-sensitively.
+pub fn text_atom(text: &str) -> Atom {
+    concrete::TextAtom {
+        matcher: TextMatcher {
+            matcher: Matcher {
+                matcher: either::Left(either::Left(text.to_string())),
+                negate: false,
+                case_sensitive: true,
+                empty_always_false: true,
+                empty_match: None,
+            },
+            set: None,
+        },
+    }
+    .into()
+}
+
+/// An atom matching if one of the chunks of the token is `chunk`.
+pub fn chunk_atom(chunk: &str) -> Atom {
+    concrete::ChunkAtom {
+        matcher: Matcher {
+            matcher: either::Left(either::Left(chunk.to_string())),
+            negate: false,
+            case_sensitive: true,
+            empty_always_false: true,
+            empty_match: None,
+        },
+    }
+    .into()
+}
+
+/// An atom matching if one of the chunks of the token is in `chunks` or, if `negate` is true, if none is.
+pub fn chunk_set_atom(chunks: &[&str], negate: bool) -> Atom {
+    concrete::ChunkSetAtom {
+        matcher: SetMatcher {
+            set: chunks.iter().map(|x| x.to_string()).collect(),
+            negate,
+        },
+    }
+    .into()
+}
+
+/// An atom matching if the token has any of the part-of-speech tags `tags`.
+pub fn pos_set_atom(tags: &[&str]) -> Atom {
+    concrete::PosSetAtom {
+        tags: tags.iter().map(|x| x.to_string()).collect(),
+    }
+    .into()
+}
+
+/// An atom matching if any of `atoms` matches. Unlike `OrAtom::or` the atoms are never combined.
+pub fn or_atom(atoms: Vec<Atom>) -> Atom {
+    OrAtom { atoms }.into()
+}
+
+/// An atom matching if `atom` does not match. Unlike `NotAtom::not` the atom is never combined.
+pub fn not_atom(atom: Atom) -> Atom {
+    NotAtom {
+        atom: Box::new(atom),
+    }
+    .into()
+}
+
+/// A composition in which each atom must match exactly one token. The group ids are the indices of the groups.
+pub fn composition(atoms: Vec<Atom>) -> Composition {
+    let n_parts = atoms.len();
+
+    Composition {
+        parts: atoms
+            .into_iter()
+            .map(|atom| Part {
+                atom,
+                quantifier: Quantifier { min: 1, max: 1 },
+                visible: true,
+            })
+            .collect(),
+        group_ids_to_idx: (0..=n_parts).map(|i| (i, i)).collect(),
+        can_stop_mask: vec![false; n_parts],
+    }
+}
+
+fn synthesizer(text: &str) -> Synthesizer {
+    Synthesizer {
+        use_titlecase_adjust: false,
+        sentence_case: false,
+        parts: vec![SynthesizerPart::Text(text.to_string())],
+    }
+}
+
+/// A rule which suggests to replace the token `text` with `replacement`.
+pub fn rule(id: &str, text: &str, replacement: &str) -> Rule {
+    phrase_rule(id, &[text], replacement)
+}
+
+/// A rule which suggests to replace the consecutive tokens `texts` with `replacement`.
+pub fn phrase_rule(id: &str, texts: &[&str], replacement: &str) -> Rule {
+    atom_rule(
+        id,
+        texts.iter().map(|x| text_atom(x)).collect(),
+        replacement,
+    )
+}
+
+/// A rule which suggests to replace the consecutive tokens matched by `atoms` with `replacement`.
+pub fn atom_rule(id: &str, atoms: Vec<Atom>, replacement: &str) -> Rule {
+    let n_atoms = atoms.len();
+
+    Rule {
+        id: id.to_string(),
+        engine: Engine::Token(TokenEngine {
+            composition: composition(atoms),
+            antipatterns: Vec::new(),
+        }),
+        examples: Vec::new(),
+        suggesters: vec![synthesizer(replacement)],
+        message: synthesizer(id),
+        start: 1,
+        end: n_atoms + 1,
+        on: true,
+        first_match_only: false,
+        language: None,
+        confidence: None,
+        priority: 0,
+        url: None,
+        short: None,
+        name: id.to_string(),
+        category_id: String::new(),
+        category_name: String::new(),
+        category_type: None,
+    }
+}